@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use crate::{SourceMap, Token, token::TokenChunk};
+use crate::{SourceMap, Token, sourcemap::resolve_source, token::TokenChunk};
 
 /// The `ConcatSourceMapBuilder` is a helper to concat sourcemaps.
 ///
@@ -29,6 +29,12 @@ pub struct ConcatSourceMapBuilder<'a> {
     pub(crate) token_chunks: Vec<TokenChunk>,
     pub(crate) token_chunk_prev_source_id: u32,
     pub(crate) token_chunk_prev_name_id: u32,
+    /// Set explicitly with [`set_debug_id`](Self::set_debug_id); takes precedence over the inputs'.
+    pub(crate) debug_id: Option<Cow<'a, str>>,
+    /// The `debug_id` shared by the inputs, if any carried one.
+    pub(crate) input_debug_id: Option<Cow<'a, str>>,
+    /// Set once two inputs carried different `debug_id`s.
+    pub(crate) debug_id_conflict: bool,
 }
 
 impl<'a> ConcatSourceMapBuilder<'a> {
@@ -52,6 +58,9 @@ impl<'a> ConcatSourceMapBuilder<'a> {
             token_chunks: Vec::with_capacity(token_chunks_len),
             token_chunk_prev_source_id: 0,
             token_chunk_prev_name_id: 0,
+            debug_id: None,
+            input_debug_id: None,
+            debug_id_conflict: false,
         }
     }
 
@@ -77,6 +86,27 @@ impl<'a> ConcatSourceMapBuilder<'a> {
         self.source_contents.resize(self.sources.len(), None);
     }
 
+    /// Record an input's `debug_id`, noting a conflict when it differs from an earlier input's.
+    fn collect_debug_id(&mut self, debug_id: Option<Cow<'a, str>>) {
+        let Some(debug_id) = debug_id else { return };
+        match &self.input_debug_id {
+            None => self.input_debug_id = Some(debug_id),
+            Some(existing) => self.debug_id_conflict |= *existing != debug_id,
+        }
+    }
+
+    /// Set the `debug_id` of the combined map, overriding any carried by the inputs.
+    /// Borrowed for `'a` (no allocation).
+    pub fn set_debug_id(&mut self, debug_id: &'a str) {
+        self.debug_id = Some(Cow::Borrowed(debug_id));
+    }
+
+    /// Whether the inputs carried different `debug_id`s. The combined map then has no `debug_id`
+    /// unless one is set with [`set_debug_id`](Self::set_debug_id).
+    pub fn has_debug_id_conflict(&self) -> bool {
+        self.debug_id_conflict
+    }
+
     /// Create new `ConcatSourceMapBuilder` from an array of `SourceMap`s and line offsets.
     ///
     /// This avoids memory copies versus creating builder with `ConcatSourceMapBuilder::default()`
@@ -141,18 +171,56 @@ impl<'a> ConcatSourceMapBuilder<'a> {
     /// outlive it. Use [`add_sourcemap_owned`](Self::add_sourcemap_owned) when you own the map and
     /// want to move its strings in instead.
     pub fn add_sourcemap(&mut self, sourcemap: &'a SourceMap<'_>, line_offset: u32) {
+        let (source_offset, name_offset) = self.extend_borrowed_strings(sourcemap);
+        self.add_tokens(&sourcemap.tokens, line_offset, source_offset, name_offset);
+    }
+
+    /// Like [`add_sourcemap`](Self::add_sourcemap), for code joined onto the *same* line as what
+    /// precedes it (e.g. inside an IIFE wrapper): tokens on `sourcemap`'s first generated line are
+    /// also shifted right by `first_line_col_offset` columns.
+    pub fn add_sourcemap_with_col_offset(
+        &mut self,
+        sourcemap: &'a SourceMap<'_>,
+        line_offset: u32,
+        first_line_col_offset: u32,
+    ) {
+        if first_line_col_offset == 0 {
+            return self.add_sourcemap(sourcemap, line_offset);
+        }
+        let (source_offset, name_offset) = self.extend_borrowed_strings(sourcemap);
+        let tokens: Vec<Token> = sourcemap
+            .tokens
+            .iter()
+            .map(|&(mut token)| {
+                if token.dst_line == 0 {
+                    token.dst_col = token.dst_col.saturating_add(first_line_col_offset);
+                }
+                token
+            })
+            .collect();
+        self.add_tokens(&tokens, line_offset, source_offset, name_offset);
+    }
+
+    /// Extend names/sources/sourcesContent with borrows of `sourcemap`'s, returning the source
+    /// and name id offsets its tokens need.
+    fn extend_borrowed_strings(&mut self, sourcemap: &'a SourceMap<'_>) -> (u32, u32) {
         let source_offset = self.sources.len() as u32;
         let name_offset = self.names.len() as u32;
 
         // Borrow strings directly from the input map — no allocations. The output `SourceMap`'s
         // lifetime is tied to `'a`, so the borrow checker enforces that input maps outlive it.
-        self.sources.extend(sourcemap.get_sources().map(Cow::Borrowed));
+        // The combined map has no `sourceRoot`, so an input's root is folded into its sources
+        // (the only case that allocates).
+        let source_root = sourcemap.get_source_root();
+        self.sources
+            .extend(sourcemap.get_sources().map(|source| resolve_source(source_root, source)));
         self.source_contents
             .extend(sourcemap.get_source_contents().map(|content| content.map(Cow::Borrowed)));
         self.pad_source_contents();
         self.names.extend(sourcemap.get_names().map(Cow::Borrowed));
+        self.collect_debug_id(sourcemap.get_debug_id().map(Cow::Borrowed));
 
-        self.add_tokens(&sourcemap.tokens, line_offset, source_offset, name_offset);
+        (source_offset, name_offset)
     }
 
     /// Add an **owned** `SourceMap` to the concatenation, **moving** its strings in (no copy),
@@ -169,11 +237,23 @@ impl<'a> ConcatSourceMapBuilder<'a> {
 
         let parts = sourcemap.into_parts();
 
-        // Move the owned entries in — no string bytes are copied.
-        self.sources.extend(parts.sources);
+        // Move the owned entries in — no string bytes are copied unless a `sourceRoot` has to be
+        // folded into the sources.
+        match parts.source_root.as_deref() {
+            Some(source_root) if !source_root.is_empty() => {
+                self.sources.extend(parts.sources.into_iter().map(|source| {
+                    match resolve_source(Some(source_root), &source) {
+                        Cow::Owned(resolved) => Cow::Owned(resolved),
+                        Cow::Borrowed(_) => source,
+                    }
+                }));
+            }
+            _ => self.sources.extend(parts.sources),
+        }
         self.source_contents.extend(parts.source_contents);
         self.pad_source_contents();
         self.names.extend(parts.names);
+        self.collect_debug_id(parts.debug_id);
 
         self.add_tokens(&parts.tokens, line_offset, source_offset, name_offset);
     }
@@ -189,79 +269,20 @@ impl<'a> ConcatSourceMapBuilder<'a> {
         source_offset: u32,
         name_offset: u32,
     ) {
-        let start = self.tokens.len();
-        // The chunk header records the prev-id baseline as it stood *before* this chunk.
-        let chunk_prev_source_id = self.token_chunk_prev_source_id;
-        let chunk_prev_name_id = self.token_chunk_prev_name_id;
-
-        if start == 0 && line_offset == 0 && source_offset == 0 && name_offset == 0 {
-            // Genuinely the first contributing map: no line/source/name offset, and no previous
-            // token to dedup against, so every token is unchanged — copy them in one `memcpy`.
-            // (A prior map can add sources/names without tokens, leaving `start == 0` while the
-            // offsets are non-zero, so all four conditions must hold to skip translation.)
-            self.tokens.extend_from_slice(tokens);
-        } else {
-            self.tokens.reserve(tokens.len());
-            let mut tokens = tokens.iter();
-            // Boundary dedup: only the first token can equal the previous map's last token (every
-            // later token has a distinct generated position), so check it once and drop if equal.
-            if let Some(first) = tokens.next() {
-                let first = first.translated(line_offset, source_offset, name_offset);
-                if self.tokens.last() != Some(&first) {
-                    self.tokens.push(first);
-                }
-            }
-            self.tokens.extend(
-                tokens.map(|token| token.translated(line_offset, source_offset, name_offset)),
-            );
-        }
-
-        // The next chunk's VLQ baseline is the last source/name id committed. Scan back from the
-        // end of what we just appended — the final token almost always carries both, so this is
-        // typically O(1); if this map contributed neither, the previous baseline carries over.
-        let mut prev_source_id = chunk_prev_source_id;
-        let mut prev_name_id = chunk_prev_name_id;
-        let (mut have_source, mut have_name) = (false, false);
-        for token in self.tokens[start..].iter().rev() {
-            if !have_source && let Some(id) = token.get_source_id() {
-                prev_source_id = id;
-                have_source = true;
-            }
-            if !have_name && let Some(id) = token.get_name_id() {
-                prev_name_id = id;
-                have_name = true;
-            }
-            if have_source && have_name {
-                break;
-            }
-        }
-        self.token_chunk_prev_source_id = prev_source_id;
-        self.token_chunk_prev_name_id = prev_name_id;
-
-        // Record the chunk once boundary dedup has settled the actual end index.
-        let end = self.tokens.len() as u32;
-        let chunk = if start > 0 {
-            let prev = &self.tokens[start - 1];
-            TokenChunk::new(
-                start as u32,
-                end,
-                prev.get_dst_line(),
-                prev.get_dst_col(),
-                prev.get_src_line(),
-                prev.get_src_col(),
-                chunk_prev_name_id,
-                chunk_prev_source_id,
-            )
-        } else {
-            TokenChunk::new(0, end, 0, 0, 0, 0, 0, 0)
-        };
+        let chunk = append_tokens(
+            &mut self.tokens,
+            tokens,
+            (line_offset, source_offset, name_offset),
+            &mut self.token_chunk_prev_source_id,
+            &mut self.token_chunk_prev_name_id,
+        );
         self.token_chunks.push(chunk);
     }
 
     /// Finish, moving the accumulated names/sources/contents straight into a [`SourceMap<'a>`]
     /// (zero copy — the `Cow` vectors are moved, not rebuilt).
     pub fn into_sourcemap(self) -> SourceMap<'a> {
-        SourceMap::new(
+        let mut sourcemap = SourceMap::new(
             None,
             self.names,
             None,
@@ -269,7 +290,11 @@ impl<'a> ConcatSourceMapBuilder<'a> {
             self.source_contents,
             self.tokens.into_boxed_slice(),
             Some(self.token_chunks),
-        )
+        );
+        // Inputs agreeing on one `debug_id` keep it; conflicting ones are dropped.
+        sourcemap.debug_id =
+            self.debug_id.or(if self.debug_id_conflict { None } else { self.input_debug_id });
+        sourcemap
     }
 
     /// Same as [`Self::into_sourcemap`], but detaches to a `'static` [`crate::OwnedSourceMap`].
@@ -281,6 +306,85 @@ impl<'a> ConcatSourceMapBuilder<'a> {
     }
 }
 
+/// Append `tokens` to `dst` for a concatenation, translated by `(line, source, name)` offsets, and
+/// return the [`TokenChunk`] covering them.
+///
+/// `prev_source_id_out` / `prev_name_id_out` carry the last source/name id committed to `dst`
+/// (the VLQ baseline of the next chunk) and are updated. When the first token equals `dst`'s last
+/// one (maps sharing a boundary position), it is dropped.
+pub(crate) fn append_tokens(
+    dst: &mut Vec<Token>,
+    tokens: &[Token],
+    (line_offset, source_offset, name_offset): (u32, u32, u32),
+    prev_source_id_out: &mut u32,
+    prev_name_id_out: &mut u32,
+) -> TokenChunk {
+    let start = dst.len();
+    // The chunk header records the prev-id baseline as it stood *before* this chunk.
+    let chunk_prev_source_id = *prev_source_id_out;
+    let chunk_prev_name_id = *prev_name_id_out;
+
+    if start == 0 && line_offset == 0 && source_offset == 0 && name_offset == 0 {
+        // Genuinely the first contributing map: no line/source/name offset, and no previous
+        // token to dedup against, so every token is unchanged — copy them in one `memcpy`.
+        // (A prior map can add sources/names without tokens, leaving `start == 0` while the
+        // offsets are non-zero, so all four conditions must hold to skip translation.)
+        dst.extend_from_slice(tokens);
+    } else {
+        dst.reserve(tokens.len());
+        let mut tokens = tokens.iter();
+        // Boundary dedup: only the first token can equal the previous map's last token (every
+        // later token has a distinct generated position), so check it once and drop if equal.
+        if let Some(first) = tokens.next() {
+            let first = first.translated(line_offset, source_offset, name_offset);
+            if dst.last() != Some(&first) {
+                dst.push(first);
+            }
+        }
+        dst.extend(tokens.map(|token| token.translated(line_offset, source_offset, name_offset)));
+    }
+
+    // The next chunk's VLQ baseline is the last source/name id committed. Scan back from the
+    // end of what we just appended — the final token almost always carries both, so this is
+    // typically O(1); if this map contributed neither, the previous baseline carries over.
+    let mut prev_source_id = chunk_prev_source_id;
+    let mut prev_name_id = chunk_prev_name_id;
+    let (mut have_source, mut have_name) = (false, false);
+    for token in dst[start..].iter().rev() {
+        if !have_source && let Some(id) = token.get_source_id() {
+            prev_source_id = id;
+            have_source = true;
+        }
+        if !have_name && let Some(id) = token.get_name_id() {
+            prev_name_id = id;
+            have_name = true;
+        }
+        if have_source && have_name {
+            break;
+        }
+    }
+    *prev_source_id_out = prev_source_id;
+    *prev_name_id_out = prev_name_id;
+
+    // Record the chunk once boundary dedup has settled the actual end index.
+    let end = dst.len() as u32;
+    if start > 0 {
+        let prev = &dst[start - 1];
+        TokenChunk::new(
+            start as u32,
+            end,
+            prev.get_dst_line(),
+            prev.get_dst_col(),
+            prev.get_src_line(),
+            prev.get_src_col(),
+            chunk_prev_name_id,
+            chunk_prev_source_id,
+        )
+    } else {
+        TokenChunk::new(0, end, 0, 0, 0, 0, 0, 0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -571,4 +675,110 @@ mod tests {
         assert_eq!(concat_sm.tokens[1], Token::new(2, 3, 0, 0, None, None));
         assert_eq!(concat_sm.tokens[2], Token::new(3, 9, 0, 0, None, None));
     }
+
+    #[test]
+    fn resolves_source_roots() {
+        let with_root = |root: &'static str, source: &'static str| SourceMap {
+            source_root: Some(Cow::Borrowed(root)),
+            sources: vec![Cow::Borrowed(source)],
+            ..SourceMap::default()
+        };
+        let a = with_root("src/", "a.js");
+        let b = with_root("lib", "b.js");
+        let c = with_root("", "c.js");
+
+        let borrowed =
+            ConcatSourceMapBuilder::from_sourcemaps(&[(&a, 0), (&b, 1), (&c, 2)]).into_sourcemap();
+        assert_eq!(borrowed.get_source_root(), None);
+        assert_eq!(borrowed.get_sources().collect::<Vec<_>>(), ["src/a.js", "lib/b.js", "c.js"]);
+
+        let owned = ConcatSourceMapBuilder::from_owned_sourcemaps(vec![(a, 0), (b, 1), (c, 2)])
+            .into_sourcemap();
+        assert_eq!(owned.get_sources().collect::<Vec<_>>(), ["src/a.js", "lib/b.js", "c.js"]);
+    }
+
+    #[test]
+    fn add_sourcemap_with_col_offset() {
+        // `sourcemap1` renders as `(function(){` and `sourcemap2`'s code is joined onto that line.
+        let sourcemap1 = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![Cow::Borrowed("a.js")],
+            vec![],
+            vec![Token::new(0, 0, 0, 0, Some(0), None)].into_boxed_slice(),
+            None,
+        );
+        let sourcemap2 = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![Cow::Borrowed("b.js")],
+            vec![],
+            vec![Token::new(0, 0, 0, 0, Some(0), None), Token::new(1, 2, 1, 2, Some(0), None)]
+                .into_boxed_slice(),
+            None,
+        );
+
+        let mut builder = ConcatSourceMapBuilder::default();
+        builder.add_sourcemap(&sourcemap1, 0);
+        builder.add_sourcemap_with_col_offset(&sourcemap2, 0, 12);
+        let sm = builder.into_sourcemap();
+
+        assert_eq!(
+            sm.get_tokens().collect::<Vec<_>>(),
+            vec![
+                Token::new(0, 0, 0, 0, Some(0), None),
+                Token::new(0, 12, 0, 0, Some(1), None),
+                // Only the first generated line is shifted.
+                Token::new(1, 2, 1, 2, Some(1), None),
+            ]
+        );
+
+        // Columns past `u32::MAX` saturate rather than overflow.
+        let sourcemap3 = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![Cow::Borrowed("c.js")],
+            vec![],
+            vec![Token::new(0, u32::MAX, 0, 0, Some(0), None)].into_boxed_slice(),
+            None,
+        );
+        let mut builder = ConcatSourceMapBuilder::default();
+        builder.add_sourcemap_with_col_offset(&sourcemap3, 0, 5);
+        assert_eq!(
+            builder.into_sourcemap().get_tokens().collect::<Vec<_>>(),
+            vec![Token::new(0, u32::MAX, 0, 0, Some(0), None)]
+        );
+    }
+
+    #[test]
+    fn debug_id() {
+        const ID: &str = "56431d54-c0a6-451d-8ea2-ba5de5d8ca2e";
+        let with_id = |debug_id: Option<&'static str>| SourceMap {
+            debug_id: debug_id.map(Cow::Borrowed),
+            ..SourceMap::default()
+        };
+        let (a, b, none) = (with_id(Some(ID)), with_id(Some(ID)), with_id(None));
+
+        let builder = ConcatSourceMapBuilder::from_sourcemaps(&[(&a, 0), (&none, 1), (&b, 2)]);
+        assert!(!builder.has_debug_id_conflict());
+        assert_eq!(builder.into_sourcemap().get_debug_id(), Some(ID));
+
+        let owned = ConcatSourceMapBuilder::from_owned_sourcemaps(vec![(a.clone(), 0), (b, 1)]);
+        assert_eq!(owned.into_sourcemap().get_debug_id(), Some(ID));
+
+        let other = with_id(Some("00000000-0000-0000-0000-000000000000"));
+        let mut builder = ConcatSourceMapBuilder::from_sourcemaps(&[(&a, 0), (&other, 1)]);
+        assert!(builder.has_debug_id_conflict());
+        assert_eq!(
+            ConcatSourceMapBuilder::from_sourcemaps(&[(&a, 0), (&other, 1)])
+                .into_sourcemap()
+                .get_debug_id(),
+            None
+        );
+        builder.set_debug_id(ID);
+        assert_eq!(builder.into_sourcemap().get_debug_id(), Some(ID));
+    }
 }
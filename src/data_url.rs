@@ -0,0 +1,171 @@
+use std::{error, fmt};
+
+use crate::{Error, SourceMap};
+
+/// Why [`SourceMap::from_data_url`] rejected its input.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DataUrlError {
+    /// The input does not start with `data:` or has no `,` before the payload.
+    NotDataUrl,
+    /// The media type is missing or is not `application/json`.
+    UnsupportedMediaType,
+    /// The payload is marked `base64` but is not valid base64.
+    InvalidBase64,
+    /// The payload is not marked `base64` and has a `%` not followed by two hex digits.
+    InvalidPercentEncoding,
+    /// The payload decoded, but is not a valid source map.
+    InvalidSourceMap(Error),
+}
+
+impl fmt::Display for DataUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotDataUrl => write!(f, "Not a data URL"),
+            Self::UnsupportedMediaType => write!(f, "Data URL media type is not application/json"),
+            Self::InvalidBase64 => write!(f, "Data URL payload could not be decoded"),
+            Self::InvalidPercentEncoding => {
+                write!(f, "Data URL payload has an invalid percent-encoding")
+            }
+            Self::InvalidSourceMap(err) => write!(f, "Data URL payload is invalid: {err}"),
+        }
+    }
+}
+
+impl error::Error for DataUrlError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        if let Self::InvalidSourceMap(err) = self { Some(err) } else { None }
+    }
+}
+
+impl SourceMap<'static> {
+    /// Decode a source map from a `data:` URL, such as one produced by [`SourceMap::to_data_url`]
+    /// or found in a `//# sourceMappingURL=` comment.
+    ///
+    /// The parameters before the `,` may come in any order and are matched case-insensitively:
+    /// `application/json` is required, a `charset` is ignored, and `base64` is also recognized
+    /// when glued to the previous parameter without a `;` (as in `charset=utf-8base64`). Without
+    /// `base64`, the payload is percent-decoded.
+    pub fn from_data_url(url: &str) -> Result<Self, DataUrlError> {
+        let header_end = url.find(',').ok_or(DataUrlError::NotDataUrl)?;
+        let (header, payload) = (&url[..header_end], &url[header_end + 1..]);
+        if !header.get(..5).is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:")) {
+            return Err(DataUrlError::NotDataUrl);
+        }
+
+        let mut is_json = false;
+        let mut is_base64 = false;
+        for param in header[5..].split(';') {
+            let mut param = param.trim();
+            if let Some(rest) = strip_suffix_ignore_case(param, "base64") {
+                is_base64 = true;
+                param = rest.trim();
+            }
+            if param.contains('/') {
+                is_json = param.eq_ignore_ascii_case("application/json");
+            }
+        }
+        if !is_json {
+            return Err(DataUrlError::UnsupportedMediaType);
+        }
+
+        let json = if is_base64 {
+            base64_simd::STANDARD
+                .decode_to_vec(payload.trim_end())
+                .map_err(|_| DataUrlError::InvalidBase64)?
+        } else {
+            percent_decode(payload).ok_or(DataUrlError::InvalidPercentEncoding)?
+        };
+        SourceMap::from_json_slice(&json)
+            .map(SourceMap::into_owned)
+            .map_err(DataUrlError::InvalidSourceMap)
+    }
+}
+
+fn strip_suffix_ignore_case<'s>(s: &'s str, suffix: &str) -> Option<&'s str> {
+    let start = s.len().checked_sub(suffix.len())?;
+    (s.is_char_boundary(start) && s[start..].eq_ignore_ascii_case(suffix)).then(|| &s[..start])
+}
+
+fn percent_decode(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut bytes = input.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let hex = [bytes.next()?, bytes.next()?];
+            // `from_str_radix` alone would also accept `+7`.
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            out.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            out.push(byte);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r#"{"version":3,"sources":["a.js"],"names":[],"mappings":"AAAA"}"#;
+
+    fn base64() -> String {
+        base64_simd::STANDARD.encode_to_string(JSON)
+    }
+
+    #[test]
+    fn well_formed() {
+        let sm = SourceMap::from_json_string(JSON).unwrap();
+        let roundtrip = SourceMap::from_data_url(&sm.to_data_url()).unwrap();
+        assert_eq!(roundtrip.to_json_string(), sm.to_json_string());
+
+        let payload = base64();
+        for header in [
+            "data:application/json;base64",
+            "data:application/json;charset=utf-8;base64",
+            "data:charset=utf-8;application/json;base64",
+            "data:application/json;charset=UTF-8base64",
+            "DATA:Application/JSON;BASE64",
+        ] {
+            let sm = SourceMap::from_data_url(&format!("{header},{payload}"))
+                .unwrap_or_else(|err| panic!("{header}: {err}"));
+            assert_eq!(sm.get_source(0), Some("a.js"), "{header}");
+        }
+
+        let percent_encoded = JSON.replace('"', "%22").replace(',', "%2C");
+        let sm = SourceMap::from_data_url(&format!("data:application/json,{percent_encoded}"));
+        assert_eq!(sm.unwrap().get_source(0), Some("a.js"));
+    }
+
+    #[test]
+    fn malformed() {
+        let payload = base64();
+        let error = |url: &str| SourceMap::from_data_url(url).unwrap_err();
+        assert!(matches!(error("application/json;base64,e30="), DataUrlError::NotDataUrl));
+        assert!(matches!(error("data:application/json;base64"), DataUrlError::NotDataUrl));
+        assert!(matches!(error("http://x,y"), DataUrlError::NotDataUrl));
+        assert!(matches!(
+            error(&format!("data:text/plain;base64,{payload}")),
+            DataUrlError::UnsupportedMediaType
+        ));
+        assert!(matches!(
+            error(&format!("data:;base64,{payload}")),
+            DataUrlError::UnsupportedMediaType
+        ));
+        assert!(matches!(
+            error("data:application/json;base64,not base64!"),
+            DataUrlError::InvalidBase64
+        ));
+        for payload in ["%zz", "%+7", "%7"] {
+            assert!(matches!(
+                error(&format!("data:application/json,{payload}")),
+                DataUrlError::InvalidPercentEncoding
+            ));
+        }
+        let err = error("data:application/json;base64,e30=");
+        assert!(matches!(err, DataUrlError::InvalidSourceMap(_)));
+        assert!(error::Error::source(&err).is_some());
+    }
+}
@@ -0,0 +1,74 @@
+//! Helpers for the `debugId` field, a UUID correlating a sourcemap with its generated file.
+//!
+//! See <https://github.com/tc39/ecma426/blob/main/proposals/debug-id.md>.
+
+/// Prefix of the comment announcing the debug id in the generated file, the code-side
+/// counterpart of the map's `debugId` field.
+pub const COMMENT_PREFIX: &str = "//# debugId=";
+
+/// Whether `id` is a UUID in its canonical hyphenated form, e.g.
+/// `56431d54-c0a6-451d-8ea2-ba5de5d8ca2e`. Hex digits may be of either case.
+pub fn is_valid_uuid(id: &str) -> bool {
+    id.len() == 36
+        && id.bytes().enumerate().all(|(i, b)| match i {
+            8 | 13 | 18 | 23 => b == b'-',
+            _ => b.is_ascii_hexdigit(),
+        })
+}
+
+/// Namespace of the name-based ids from [`uuid_from_seed`]: the version 5 UUID of
+/// `https://github.com/oxc-project/oxc-sourcemap` in the URL namespace.
+const NAMESPACE: uuid::Uuid = uuid::Uuid::from_u128(0xc264_5e0e_2bcd_54f7_8083_4e3a_0b47_be8e);
+
+/// A random (version 4) UUID.
+#[cfg(feature = "random-debug-id")]
+pub fn random_uuid() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Derive a name-based (version 5) UUID from `seed`, so the same seed always gives the same id.
+pub fn uuid_from_seed(seed: &[u8]) -> String {
+    uuid::Uuid::new_v5(&NAMESPACE, seed).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_uuids() {
+        assert!(is_valid_uuid("56431d54-c0a6-451d-8ea2-ba5de5d8ca2e"));
+        assert!(is_valid_uuid("56431D54-C0A6-451D-8EA2-BA5DE5D8CA2E"));
+        assert!(!is_valid_uuid(""));
+        assert!(!is_valid_uuid("56431d54c0a6451d8ea2ba5de5d8ca2e"));
+        assert!(!is_valid_uuid("56431d54-c0a6-451d-8ea2-ba5de5d8ca2g"));
+        assert!(!is_valid_uuid("56431d54-c0a6-451d-8ea2-ba5de5d8ca2e0"));
+    }
+
+    #[test]
+    fn generated_uuids() {
+        let id = uuid_from_seed(b"console.log(1);\n");
+        assert_eq!(id, uuid_from_seed(b"console.log(1);\n"));
+        assert_ne!(id, uuid_from_seed(b"console.log(2);\n"));
+        assert!(is_valid_uuid(&id));
+        assert_eq!(
+            NAMESPACE,
+            uuid::Uuid::new_v5(
+                &uuid::Uuid::NAMESPACE_URL,
+                b"https://github.com/oxc-project/oxc-sourcemap"
+            )
+        );
+        assert_eq!(&id[14..15], "5");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+    }
+
+    #[cfg(feature = "random-debug-id")]
+    #[test]
+    fn random_uuids() {
+        let id = random_uuid();
+        assert!(is_valid_uuid(&id));
+        assert_ne!(id, random_uuid());
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+    }
+}
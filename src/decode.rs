@@ -2,70 +2,83 @@
 /// It is a helper for decoding VLQ sourcemap strings to `SourceMap`.
 use std::borrow::Cow;
 
+use serde::de::IgnoredAny;
+
 use crate::error::{Error, Result};
+use crate::sourcemap::SourceMapCache;
 use crate::token::INVALID_ID;
 use crate::{SourceMap, Token};
 
 /// See <https://github.com/tc39/source-map/blob/1930e58ffabefe54038f7455759042c6e3dd590e/source-map-rev3.md>.
-#[derive(serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct JSONSourceMap {
     /// The version field, must be 3.
-    #[serde(deserialize_with = "deserialize_version")]
     pub version: u32,
     /// An optional name of the generated code that this source map is associated with.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub file: Option<String>,
     /// A string with the encoded mapping data.
     pub mappings: String,
     /// An optional source root, useful for relocating source files on a server or removing repeated values in the "sources" entry.
     /// This value is prepended to the individual entries in the "source" field.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub source_root: Option<String>,
     /// A list of original sources used by the "mappings" entry.
     pub sources: Vec<String>,
     /// An optional list of source content, useful when the "source" can't be hosted.
     /// The contents are listed in the same order as the sources in line 5. "null" may be used if some original sources should be retrieved by name.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sources_content: Option<Vec<Option<String>>>,
     /// A list of symbol names used by the "mappings" entry.
     #[serde(default)]
     pub names: Vec<String>,
     /// An optional field containing the debugId for this sourcemap.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub debug_id: Option<String>,
     /// Identifies third-party sources (such as framework code or bundler-generated code), allowing developers to avoid code that they don't want to see or step through, without having to configure this beforehand.
     /// The `x_google_ignoreList` field refers to the `sources` array, and lists the indices of all the known third-party sources in that source map.
     /// When parsing the source map, developer tools can use this to determine sections of the code that the browser loads and runs that could be automatically ignore-listed.
-    #[serde(rename = "x_google_ignoreList", alias = "ignoreList")]
+    #[serde(
+        rename = "x_google_ignoreList",
+        alias = "ignoreList",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub x_google_ignore_list: Option<Vec<u32>>,
 }
 
-fn deserialize_version<'de, D>(deserializer: D) -> std::result::Result<u32, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    use serde::Deserialize;
-    let version = u32::deserialize(deserializer)?;
-    if version != 3 {
-        return Err(serde::de::Error::custom(format!("unsupported source map version: {version}")));
-    }
-    Ok(version)
+fn validate_version(version: u32) -> Result<()> {
+    if version == 3 { Ok(()) } else { Err(Error::UnsupportedVersion(version)) }
 }
 
 pub fn decode(json: JSONSourceMap) -> Result<SourceMap<'static>> {
+    validate_version(json.version)?;
     validate_x_google_ignore_list(json.x_google_ignore_list.as_deref(), json.sources.len())?;
 
-    let tokens = decode_mapping(&json.mappings, json.names.len(), json.sources.len())?;
+    let tokens = decode_mapping(
+        &json.mappings,
+        json.names.len(),
+        json.sources.len(),
+        &DecodeOptions::default(),
+    )?;
+    let sources_len = json.sources.len();
     Ok(SourceMap {
         file: json.file.map(Cow::Owned),
         names: json.names.into_iter().map(Cow::Owned).collect(),
         source_root: json.source_root.map(Cow::Owned),
         sources: json.sources.into_iter().map(Cow::Owned).collect(),
-        source_contents: json
-            .sources_content
-            .map(|content| content.into_iter().map(|c| c.map(Cow::Owned)).collect())
-            .unwrap_or_default(),
+        source_contents: reconcile_sources_content(
+            json.sources_content
+                .map(|content| content.into_iter().map(|c| c.map(Cow::Owned)).collect()),
+            sources_len,
+            &DecodeOptions::default(),
+        )?,
         tokens: tokens.into_boxed_slice(),
         token_chunks: None,
         x_google_ignore_list: json.x_google_ignore_list,
         debug_id: json.debug_id.map(Cow::Owned),
+        token_ends: None,
+        cache: SourceMapCache::default(),
     })
 }
 
@@ -74,11 +87,12 @@ pub fn decode(json: JSONSourceMap) -> Result<SourceMap<'static>> {
 /// lifetime: each `Cow::Borrowed` is a zero-copy slice into that buffer, and
 /// only escaped strings (which serde_json must unescape into a fresh `String`)
 /// land in `Cow::Owned`.
+///
+/// `C` is the `sourcesContent` type, [`IgnoredAny`] to skip over it without allocating.
 #[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct BorrowedJSONSourceMap<'a> {
-    #[serde(deserialize_with = "deserialize_version")]
-    #[expect(dead_code)]
+#[serde(rename_all = "camelCase", bound(deserialize = "C: serde::Deserialize<'de>"))]
+struct BorrowedJSONSourceMap<'a, C = Vec<Option<Cow<'a, str>>>> {
+    /// Checked after parsing, so an unsupported version is [`Error::UnsupportedVersion`].
     version: u32,
     #[serde(borrow)]
     file: Option<Cow<'a, str>>,
@@ -88,8 +102,7 @@ struct BorrowedJSONSourceMap<'a> {
     source_root: Option<Cow<'a, str>>,
     #[serde(borrow)]
     sources: Vec<Cow<'a, str>>,
-    #[serde(borrow)]
-    sources_content: Option<Vec<Option<Cow<'a, str>>>>,
+    sources_content: Option<C>,
     #[serde(borrow, default)]
     names: Vec<Cow<'a, str>>,
     #[serde(borrow)]
@@ -98,26 +111,163 @@ struct BorrowedJSONSourceMap<'a> {
     x_google_ignore_list: Option<Vec<u32>>,
 }
 
-pub fn decode_from_string(value: &str) -> Result<SourceMap<'_>> {
-    let json: BorrowedJSONSourceMap<'_> = serde_json::from_str(value)?;
+impl<'a> BorrowedJSONSourceMap<'a, IgnoredAny> {
+    fn without_sources_content(self) -> BorrowedJSONSourceMap<'a> {
+        BorrowedJSONSourceMap {
+            version: self.version,
+            file: self.file,
+            mappings: self.mappings,
+            source_root: self.source_root,
+            sources: self.sources,
+            sources_content: None,
+            names: self.names,
+            debug_id: self.debug_id,
+            x_google_ignore_list: self.x_google_ignore_list,
+        }
+    }
+}
+
+/// Options for [`SourceMap::from_json_string_with`].
+#[derive(Debug, Clone, Default)]
+pub struct DecodeOptions {
+    /// Saturate `mappings` values that overflow (VLQs past 62 bits, or positions past `u32::MAX`)
+    /// to `u32::MAX` instead of failing with [`Error::VlqOverflow`]. Lossy, for non-conforming
+    /// producers; defaults to `false`.
+    pub clamp_overflow: bool,
+    /// Reject a `sourcesContent` whose length differs from `sources` with
+    /// [`Error::BadSourcesContentLength`]. By default a shorter one is padded with `null`s and a
+    /// longer one truncated, keeping the arrays aligned.
+    pub strict_sources_content: bool,
+    /// Skip over `sourcesContent` without allocating it, leaving every source without content.
+    /// For reading a map only for its mappings.
+    pub skip_source_contents: bool,
+}
+
+pub fn decode_from_string<'a>(value: &'a str, options: &DecodeOptions) -> Result<SourceMap<'a>> {
+    if options.skip_source_contents {
+        let json: BorrowedJSONSourceMap<'a, IgnoredAny> = serde_json::from_str(value)?;
+        return decode_borrowed(json.without_sources_content(), options);
+    }
+    decode_borrowed(serde_json::from_str(value)?, options)
+}
 
+pub fn decode_from_slice(value: &[u8]) -> Result<SourceMap<'_>> {
+    decode_borrowed(serde_json::from_slice(value)?, &DecodeOptions::default())
+}
+
+fn decode_borrowed<'a>(
+    json: BorrowedJSONSourceMap<'a>,
+    options: &DecodeOptions,
+) -> Result<SourceMap<'a>> {
+    validate_version(json.version)?;
     validate_x_google_ignore_list(json.x_google_ignore_list.as_deref(), json.sources.len())?;
 
-    let tokens = decode_mapping(&json.mappings, json.names.len(), json.sources.len())?;
+    let tokens = decode_mapping(&json.mappings, json.names.len(), json.sources.len(), options)?;
+    let source_contents =
+        reconcile_sources_content(json.sources_content, json.sources.len(), options)?;
 
     Ok(SourceMap {
         file: json.file,
         names: json.names,
         source_root: json.source_root,
         sources: json.sources,
-        source_contents: json.sources_content.unwrap_or_default(),
+        source_contents,
         tokens: tokens.into_boxed_slice(),
         token_chunks: None,
         x_google_ignore_list: json.x_google_ignore_list,
         debug_id: json.debug_id,
+        token_ends: None,
+        cache: SourceMapCache::default(),
     })
 }
 
+/// A problem [`SourceMap::from_json_bytes_lenient`] worked around.
+#[derive(Debug)]
+pub enum DecodeWarning {
+    /// The input was not source map JSON, so the map is empty.
+    InvalidJson(Error),
+    /// `mappings` was malformed; only the tokens before the bad segment were kept.
+    MalformedMappings(Error),
+    /// `sourcesContent` had this length, and was padded or truncated to match `sources`.
+    SourcesContentLength(u32),
+    /// This ignore list entry referenced a non-existing source and was dropped.
+    BadIgnoreListEntry(u32),
+}
+
+pub fn decode_lenient(value: &[u8]) -> (SourceMap<'_>, Vec<DecodeWarning>) {
+    let mut warnings = vec![];
+    let json: BorrowedJSONSourceMap = match serde_json::from_slice(value)
+        .map_err(Error::from)
+        .and_then(|json: BorrowedJSONSourceMap| validate_version(json.version).map(|()| json))
+    {
+        Ok(json) => json,
+        Err(err) => {
+            warnings.push(DecodeWarning::InvalidJson(err));
+            return (SourceMap::default(), warnings);
+        }
+    };
+    let options = DecodeOptions { clamp_overflow: true, ..DecodeOptions::default() };
+    let sources_len = json.sources.len();
+
+    let mut x_google_ignore_list = json.x_google_ignore_list;
+    if let Some(ignore_list) = x_google_ignore_list.as_mut() {
+        ignore_list.retain(|&id| {
+            let valid = (id as usize) < sources_len;
+            if !valid {
+                warnings.push(DecodeWarning::BadIgnoreListEntry(id));
+            }
+            valid
+        });
+    }
+
+    let mut tokens = vec![];
+    if let Err(err) =
+        decode_mapping_into(&json.mappings, json.names.len(), sources_len, &options, &mut tokens)
+    {
+        warnings.push(DecodeWarning::MalformedMappings(err));
+    }
+
+    if let Some(sources_content) = &json.sources_content
+        && sources_content.len() != sources_len
+    {
+        warnings.push(DecodeWarning::SourcesContentLength(sources_content.len() as u32));
+    }
+    // Only strict mode fails to reconcile.
+    let source_contents =
+        reconcile_sources_content(json.sources_content, sources_len, &options).unwrap_or_default();
+
+    let sourcemap = SourceMap {
+        file: json.file,
+        names: json.names,
+        source_root: json.source_root,
+        sources: json.sources,
+        source_contents,
+        tokens: tokens.into_boxed_slice(),
+        token_chunks: None,
+        x_google_ignore_list,
+        debug_id: json.debug_id,
+        token_ends: None,
+        cache: SourceMapCache::default(),
+    };
+    (sourcemap, warnings)
+}
+
+/// Align a present `sourcesContent` with `sources`, see [`DecodeOptions::strict_sources_content`].
+fn reconcile_sources_content<T: Clone>(
+    sources_content: Option<Vec<Option<T>>>,
+    sources_len: usize,
+    options: &DecodeOptions,
+) -> Result<Vec<Option<T>>> {
+    let Some(mut sources_content) = sources_content else { return Ok(vec![]) };
+    if sources_content.len() != sources_len {
+        if options.strict_sources_content {
+            return Err(Error::BadSourcesContentLength(sources_content.len() as u32));
+        }
+        sources_content.resize(sources_len, None);
+    }
+    Ok(sources_content)
+}
+
 fn validate_x_google_ignore_list(ignore_list: Option<&[u32]>, sources_len: usize) -> Result<()> {
     if let Some(ignore_list) = ignore_list {
         for &idx in ignore_list {
@@ -129,10 +279,30 @@ fn validate_x_google_ignore_list(ignore_list: Option<&[u32]>, sources_len: usize
     Ok(())
 }
 
-fn decode_mapping(mapping: &str, names_len: usize, sources_len: usize) -> Result<Vec<Token>> {
-    let mapping = mapping.as_bytes();
+fn decode_mapping(
+    mapping: &str,
+    names_len: usize,
+    sources_len: usize,
+    options: &DecodeOptions,
+) -> Result<Vec<Token>> {
+    let mut tokens = Vec::with_capacity(estimate_token_capacity(mapping.as_bytes()));
+    decode_mapping_into(mapping, names_len, sources_len, options, &mut tokens)?;
+    Ok(tokens)
+}
 
-    let mut tokens: Vec<Token> = Vec::with_capacity(estimate_token_capacity(mapping));
+/// Decode `mapping` into `tokens`. On error, `tokens` holds the tokens before the bad segment.
+fn decode_mapping_into(
+    mapping: &str,
+    names_len: usize,
+    sources_len: usize,
+    options: &DecodeOptions,
+    tokens: &mut Vec<Token>,
+) -> Result<()> {
+    let mapping = mapping.as_bytes();
+    let clamp = options.clamp_overflow;
+    // In clamping mode positions saturate at `u32::MAX`; otherwise they wrap as before.
+    let to_u32 =
+        |value: i64| if clamp { value.min(i64::from(u32::MAX)) as u32 } else { value as u32 };
 
     let mut dst_line = 0u32;
     let mut dst_col = 0u32;
@@ -166,14 +336,14 @@ fn decode_mapping(mapping: &str, names_len: usize, sources_len: usize) -> Result
                 cursor += 1;
             }
             _ => {
-                let nums_len = parse_vlq_segment_into(mapping, &mut cursor, &mut nums)?;
+                let nums_len = parse_vlq_segment_into(mapping, &mut cursor, &mut nums, clamp)?;
 
                 // `nums[0]` is always generated column delta.
-                let new_dst_col = i64::from(dst_col) + nums[0];
+                let new_dst_col = i64::from(dst_col).saturating_add(nums[0]);
                 if new_dst_col < 0 {
                     return Err(Error::BadSegmentSize(0)); // Negative column
                 }
-                dst_col = new_dst_col as u32;
+                dst_col = to_u32(new_dst_col);
 
                 let mut src = INVALID_ID;
                 let mut name = INVALID_ID;
@@ -184,47 +354,42 @@ fn decode_mapping(mapping: &str, names_len: usize, sources_len: usize) -> Result
                     }
 
                     // Source/name fields are also delta-encoded.
-                    let new_src_id = i64::from(src_id) + nums[1];
+                    // Saturating: with `clamp`, a delta may be `i64::MAX`/`i64::MIN`.
+                    let new_src_id = i64::from(src_id).saturating_add(nums[1]);
                     if new_src_id < 0 || new_src_id >= sources_len as i64 {
                         return Err(Error::BadSourceReference(src_id));
                     }
                     src_id = new_src_id as u32;
                     src = src_id;
 
-                    let new_src_line = i64::from(src_line) + nums[2];
+                    let new_src_line = i64::from(src_line).saturating_add(nums[2]);
                     if new_src_line < 0 {
                         return Err(Error::BadSegmentSize(0)); // Negative line
                     }
-                    src_line = new_src_line as u32;
+                    src_line = to_u32(new_src_line);
 
-                    let new_src_col = i64::from(src_col) + nums[3];
+                    let new_src_col = i64::from(src_col).saturating_add(nums[3]);
                     if new_src_col < 0 {
                         return Err(Error::BadSegmentSize(0)); // Negative column
                     }
-                    src_col = new_src_col as u32;
+                    src_col = to_u32(new_src_col);
 
                     if nums_len > 4 {
-                        name_id = (i64::from(name_id) + nums[4]) as u32;
-                        if name_id >= names_len as u32 {
-                            return Err(Error::BadNameReference(name_id));
+                        let new_name_id = i64::from(name_id).saturating_add(nums[4]);
+                        if new_name_id < 0 || new_name_id >= names_len as i64 {
+                            return Err(Error::BadNameReference(new_name_id as u32));
                         }
+                        name_id = new_name_id as u32;
                         name = name_id;
                     }
                 }
 
-                tokens.push(Token::new(
-                    dst_line,
-                    dst_col,
-                    src_line,
-                    src_col,
-                    if src == INVALID_ID { None } else { Some(src) },
-                    if name == INVALID_ID { None } else { Some(name) },
-                ));
+                tokens.push(Token::from_raw(dst_line, dst_col, src_line, src_col, src, name));
             }
         }
     }
 
-    Ok(tokens)
+    Ok(())
 }
 
 /// Fast path for the dominant sourcemap segment shapes: a 4- or 5-field
@@ -364,7 +529,14 @@ fn estimate_token_capacity(mapping: &[u8]) -> usize {
 /// `rv` for the first 5 fields (the maximum valid segment size). If the segment
 /// contains more than 5 fields, we keep parsing and counting so the caller can
 /// reject it with `BadSegmentSize` carrying the true field count.
-fn parse_vlq_segment_into(mapping: &[u8], cursor: &mut usize, rv: &mut [i64; 5]) -> Result<usize> {
+///
+/// With `clamp`, a value overflowing i64 saturates (see [`DecodeOptions::clamp_overflow`]).
+fn parse_vlq_segment_into(
+    mapping: &[u8],
+    cursor: &mut usize,
+    rv: &mut [i64; 5],
+    clamp: bool,
+) -> Result<usize> {
     // The dominant segment shape in real bundler output (~95%) is 4 or 5
     // single-byte VLQ values; decode it branch-light, leaving multi-byte
     // VLQs, other arities, and diagnostics to the general loop below.
@@ -374,7 +546,14 @@ fn parse_vlq_segment_into(mapping: &[u8], cursor: &mut usize, rv: &mut [i64; 5])
     }
 
     let mut rv_len = 0usize;
-    while let Some(value) = next_vlq(mapping, cursor)? {
+    loop {
+        let start = *cursor;
+        let value = match next_vlq(mapping, cursor) {
+            Ok(Some(value)) => value,
+            Ok(None) => break,
+            Err(Error::VlqOverflow) if clamp => skip_overflowing_vlq(mapping, start, cursor)?,
+            Err(err) => return Err(err),
+        };
         if rv_len < rv.len() {
             rv[rv_len] = value;
         }
@@ -386,12 +565,30 @@ fn parse_vlq_segment_into(mapping: &[u8], cursor: &mut usize, rv: &mut [i64; 5])
     Ok(rv_len)
 }
 
+/// Consume the rest of a VLQ starting at `start` that overflowed at `*cursor`, returning the
+/// saturated value for its sign.
+#[cold]
+fn skip_overflowing_vlq(mapping: &[u8], start: usize, cursor: &mut usize) -> Result<i64> {
+    // The overflowing byte was already consumed and may have been the last one.
+    let mut enc = B64_DECODE.0[mapping[*cursor - 1] as usize];
+    while enc >= 32 {
+        let Some(&byte) = mapping.get(*cursor) else { return Err(Error::VlqLeftover) };
+        enc = B64_DECODE.0[byte as usize];
+        if enc < 0 {
+            return Err(Error::VlqLeftover);
+        }
+        *cursor += 1;
+    }
+    let negative = B64_DECODE.0[mapping[start] as usize] & 1 == 1;
+    Ok(if negative { i64::MIN } else { i64::MAX })
+}
+
 /// Decode one VLQ value starting at `*cursor`.
 ///
 /// Returns `Ok(None)` without consuming anything when positioned at a
 /// delimiter (`,` / `;`) or end of input; otherwise consumes the value's bytes.
 #[inline(always)]
-fn next_vlq(mapping: &[u8], cursor: &mut usize) -> Result<Option<i64>> {
+pub(crate) fn next_vlq(mapping: &[u8], cursor: &mut usize) -> Result<Option<i64>> {
     let Some(&byte) = mapping.get(*cursor) else { return Ok(None) };
     let first = i64::from(B64_DECODE.0[byte as usize]);
     if first < 0 {
@@ -418,10 +615,10 @@ fn next_vlq(mapping: &[u8], cursor: &mut usize) -> Result<Option<i64>> {
         }
         *cursor += 1;
         // VLQ shift grows by 5 bits per continuation byte. Bail out before
-        // `payload << shift` could overflow i64: the largest safe shift for a
-        // 5-bit value is 62, and `shift` only ever takes multiples of 5, so
-        // the first offending shift is 65.
-        if shift > 62 {
+        // `payload << shift` could overflow i64 or set its sign bit: `shift`
+        // only ever takes multiples of 5, so at 60 only a 3-bit payload fits
+        // (bits 60..=62), and any later shift overflows.
+        if shift >= 60 && (shift > 60 || enc & 0b11111 > 0b111) {
             return Err(Error::VlqOverflow);
         }
         cur |= (enc & 0b11111) << shift;
@@ -509,7 +706,13 @@ mod tests {
     fn decode_unsupported_version() {
         let input = r#"{"version": 2, "names": [], "sources": [], "mappings": ""}"#;
         let err = SourceMap::from_json_string(input).unwrap_err();
-        assert!(matches!(err, Error::BadJson(_)));
+        assert!(matches!(err, Error::UnsupportedVersion(2)));
+
+        // `JSONSourceMap` parses any version; decoding it reports the same error.
+        let json: JSONSourceMap = serde_json::from_str(input).unwrap();
+        let err = SourceMap::from_json(json).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedVersion(2)));
+        assert_eq!(err.kind(), crate::ErrorKind::Version);
     }
 
     #[test]
@@ -662,7 +865,137 @@ mod tests {
         // on a non-delimiter byte), so it is covered here.
         let mut cursor = 0;
         let mut out = [0i64; 5];
-        let err = parse_vlq_segment_into(b"", &mut cursor, &mut out).unwrap_err();
+        let err = parse_vlq_segment_into(b"", &mut cursor, &mut out, false).unwrap_err();
         assert!(matches!(err, Error::VlqNoValues));
     }
+
+    #[test]
+    fn decode_clamp_overflow() {
+        // A 14-byte VLQ overflows i64; with clamping the column saturates to `u32::MAX`.
+        let mappings = format!("{}A,{}B", "g".repeat(13), "g".repeat(13));
+        let input =
+            format!(r#"{{"version":3,"names":[],"sources":["a.js"],"mappings":"{mappings}"}}"#);
+        let options = DecodeOptions { clamp_overflow: true, ..DecodeOptions::default() };
+        let sm = SourceMap::from_json_string_with(&input, &options).unwrap();
+        assert_eq!(sm.get_tokens().map(|t| t.get_dst_col()).collect::<Vec<_>>(), [u32::MAX; 2]);
+
+        // Past `u32::MAX` without overflowing i64 (`ggggggggB` is 2^39): saturates too.
+        let input = r#"{"version":3,"names":[],"sources":["a.js"],"mappings":"AAggggggggBA"}"#;
+        let sm = SourceMap::from_json_string_with(input, &options).unwrap();
+        assert_eq!(sm.get_token(0).unwrap().get_src_line(), u32::MAX);
+
+        // A negative overflow is still a negative position.
+        let input = r#"{"version":3,"names":[],"sources":["a.js"],"mappings":"hggggggggggggB"}"#;
+        let err = SourceMap::from_json_string_with(input, &options).unwrap_err();
+        assert!(matches!(err, Error::BadSegmentSize(0)));
+
+        // A dangling continuation is still malformed.
+        let input = r#"{"version":3,"names":[],"sources":["a.js"],"mappings":"gggggggggggggg"}"#;
+        let err = SourceMap::from_json_string_with(input, &options).unwrap_err();
+        assert!(matches!(err, Error::VlqLeftover));
+
+        // Strict by default.
+        let input =
+            format!(r#"{{"version":3,"names":[],"sources":["a.js"],"mappings":"{mappings}"}}"#);
+        assert!(matches!(SourceMap::from_json_string(&input), Err(Error::VlqOverflow)));
+
+        // Saturated source and name id deltas are out of range, not an arithmetic overflow.
+        let input = r#"{"version":3,"names":[],"sources":["a.js","b.js"],"mappings":"AAAA,ACAA,AgggggggggggggggBAA"}"#;
+        let err = SourceMap::from_json_string_with(input, &options).unwrap_err();
+        assert!(matches!(err, Error::BadSourceReference(1)));
+        let input = r#"{"version":3,"names":["x"],"sources":["a.js"],"mappings":"AAAAA,AAAAhggggggggggggggB"}"#;
+        let err = SourceMap::from_json_string_with(input, &options).unwrap_err();
+        assert!(matches!(err, Error::BadNameReference(0)));
+        let input = r#"{"version":3,"names":["x"],"sources":["a.js"],"mappings":"AAAAA,AAAAgggggggggggggggB"}"#;
+        let err = SourceMap::from_json_string_with(input, &options).unwrap_err();
+        assert!(matches!(err, Error::BadNameReference(u32::MAX)));
+    }
+
+    #[test]
+    fn decode_reconciles_sources_content_length() {
+        let shorter = r#"{"version":3,"names":[],"sources":["a.js","b.js"],"sourcesContent":["a"],"mappings":"AAAA,CCAA"}"#;
+        let sm = SourceMap::from_json_string(shorter).unwrap();
+        assert_eq!(sm.get_source_contents().collect::<Vec<_>>(), [Some("a"), None]);
+        assert_eq!(sm.get_source_content(1), None);
+        let owned = SourceMap::from_json(serde_json::from_str(shorter).unwrap()).unwrap();
+        assert_eq!(owned.get_source_contents().len(), 2);
+
+        let longer = r#"{"version":3,"names":[],"sources":["a.js"],"sourcesContent":["a","b"],"mappings":""}"#;
+        let sm = SourceMap::from_json_string(longer).unwrap();
+        assert_eq!(sm.get_source_contents().collect::<Vec<_>>(), [Some("a")]);
+        assert_eq!(
+            sm.to_json_string(),
+            r#"{"version":3,"names":[],"sources":["a.js"],"sourcesContent":["a"],"mappings":""}"#
+        );
+
+        let strict = DecodeOptions { strict_sources_content: true, ..DecodeOptions::default() };
+        for input in [shorter, longer] {
+            let err = SourceMap::from_json_string_with(input, &strict).unwrap_err();
+            assert!(matches!(err, Error::BadSourcesContentLength(_)));
+        }
+        let absent = r#"{"version":3,"names":[],"sources":["a.js"],"mappings":""}"#;
+        assert_eq!(
+            SourceMap::from_json_string_with(absent, &strict).unwrap().get_source_contents().len(),
+            0
+        );
+    }
+
+    #[test]
+    fn decode_skip_source_contents() {
+        let input = r#"{"version":3,"names":[],"sources":["a.js","b.js"],"sourcesContent":["let a = 1;\n",{"not":"a string"}],"mappings":"AAAA,CCAA"}"#;
+        assert!(SourceMap::from_json_string(input).is_err());
+
+        let options = DecodeOptions { skip_source_contents: true, ..DecodeOptions::default() };
+        let sm = SourceMap::from_json_string_with(input, &options).unwrap();
+        assert!(sm.source_contents.is_empty());
+        assert_eq!(sm.get_source_content(0), None);
+        assert_eq!(sm.get_sources().collect::<Vec<_>>(), ["a.js", "b.js"]);
+        assert_eq!(sm.get_tokens().count(), 2);
+    }
+
+    #[test]
+    fn decode_lenient_partial_map() {
+        let input = br#"{"version":3,"names":[],"sources":["a.js"],"sourcesContent":["a","b"],"x_google_ignoreList":[0,4],"mappings":"AAAA,CAAC;AACA,CCAA,EAAE"}"#;
+        let (sm, warnings) = SourceMap::from_json_bytes_lenient(input);
+        assert_eq!(sm.get_tokens().count(), 3);
+        assert_eq!(sm.get_source_contents().collect::<Vec<_>>(), [Some("a")]);
+        assert_eq!(sm.get_x_google_ignore_list(), Some(&[0][..]));
+        assert!(matches!(
+            warnings[..],
+            [
+                DecodeWarning::BadIgnoreListEntry(4),
+                DecodeWarning::MalformedMappings(Error::BadSourceReference(_)),
+                DecodeWarning::SourcesContentLength(2),
+            ]
+        ));
+
+        let (sm, warnings) = SourceMap::from_json_bytes_lenient(b"{\"version\":3,");
+        assert_eq!(sm.get_tokens().count(), 0);
+        assert!(matches!(warnings[..], [DecodeWarning::InvalidJson(_)]));
+
+        // Clamped id deltas are out of range, and the tokens before them are kept.
+        for (mappings, kept) in
+            [("AAAA,ACAA,AgggggggggggggggBAA", 2), ("AAAAA,AAAAgggggggggggggggB", 1)]
+        {
+            let input = format!(
+                r#"{{"version":3,"names":["x"],"sources":["a.js","b.js"],"mappings":"{mappings}"}}"#
+            );
+            let (sm, warnings) = SourceMap::from_json_bytes_lenient(input.as_bytes());
+            assert_eq!(sm.get_tokens().count(), kept, "{mappings}");
+            assert!(
+                matches!(
+                    warnings[..],
+                    [DecodeWarning::MalformedMappings(
+                        Error::BadSourceReference(_) | Error::BadNameReference(_)
+                    )]
+                ),
+                "{mappings}"
+            );
+        }
+
+        let valid = br#"{"version":3,"names":[],"sources":["a.js"],"mappings":"AAAA"}"#;
+        let (sm, warnings) = SourceMap::from_json_bytes_lenient(valid);
+        assert!(warnings.is_empty());
+        assert_eq!(sm, SourceMap::from_json_slice(valid).unwrap());
+    }
 }
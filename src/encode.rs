@@ -36,7 +36,110 @@ pub fn encode(sourcemap: &SourceMap<'_>) -> JSONSourceMap {
     }
 }
 
-pub fn encode_to_string(sourcemap: &SourceMap<'_>) -> String {
+/// Options for [`SourceMap::to_json_string_with`].
+#[derive(Debug, Clone)]
+pub struct EncodeOptions {
+    /// Omit the `sourcesContent` key when no source has content, instead of emitting an array of
+    /// `null`s. Defaults to `true`, matching [`SourceMap::to_json_string`] (and webpack).
+    pub omit_null_sources_content: bool,
+    /// The key the ignore list is written under. Both are accepted when decoding.
+    pub ignore_list_key: IgnoreListKey,
+    /// Pad `mappings` with `;` so it spans this many generated lines, for consumers that compare
+    /// the line count against the generated file. Never drops lines that have tokens.
+    pub total_generated_lines: Option<u32>,
+    /// What to emit for tokens without a source.
+    pub sourceless_tokens: SourcelessTokens,
+    /// The order of the JSON keys.
+    pub field_order: FieldOrder,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self {
+            omit_null_sources_content: true,
+            ignore_list_key: IgnoreListKey::default(),
+            total_generated_lines: None,
+            sourceless_tokens: SourcelessTokens::default(),
+            field_order: FieldOrder::default(),
+        }
+    }
+}
+
+/// JSON key for [`SourceMap::get_x_google_ignore_list`], see [`EncodeOptions::ignore_list_key`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IgnoreListKey {
+    /// `x_google_ignoreList`, the original Chrome extension key.
+    #[default]
+    XGoogleIgnoreList,
+    /// `ignoreList`, the key standardized in ECMA-426.
+    IgnoreList,
+}
+
+/// Key order of the encoded JSON, see [`EncodeOptions::field_order`]. `version` always comes
+/// first; absent optional fields are skipped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FieldOrder {
+    /// `file`, `sourceRoot`, `names`, `sources`, `sourcesContent`, the ignore list, `mappings`,
+    /// `debugId`.
+    #[default]
+    Oxc,
+    /// The order of the specification's example: `file`, `sources`, `sourceRoot`,
+    /// `sourcesContent`, `names`, `mappings`, then the ignore list and `debugId`.
+    Spec,
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    File,
+    SourceRoot,
+    Names,
+    Sources,
+    SourcesContent,
+    IgnoreList,
+    Mappings,
+    DebugId,
+}
+
+impl FieldOrder {
+    fn fields(self) -> [Field; 8] {
+        use Field::*;
+        match self {
+            Self::Oxc => {
+                [File, SourceRoot, Names, Sources, SourcesContent, IgnoreList, Mappings, DebugId]
+            }
+            Self::Spec => {
+                [File, Sources, SourceRoot, SourcesContent, Names, Mappings, IgnoreList, DebugId]
+            }
+        }
+    }
+}
+
+/// Encoding policy for tokens without a source, see [`EncodeOptions::sourceless_tokens`].
+///
+/// Such tokens encode as generated-only segments (a lone column), which some strict consumers
+/// reject. The non-default policies guarantee every segment has a source.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SourcelessTokens {
+    /// Emit generated-only segments.
+    #[default]
+    Keep,
+    /// Leave the tokens out.
+    Skip,
+    /// Map each token to the original position of the closest preceding token with a source,
+    /// without its name. Tokens before the first token with a source are left out.
+    InheritPrevious,
+}
+
+impl IgnoreListKey {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::XGoogleIgnoreList => "x_google_ignoreList",
+            Self::IgnoreList => "ignoreList",
+        }
+    }
+}
+
+pub fn encode_to_string(sourcemap: &SourceMap<'_>, options: &EncodeOptions) -> String {
     // Worst-case capacity accounting:
     // - escape_into may write up to (len * 2 + 2) for each string
     // - include commas between items and constant JSON punctuation/keys
@@ -78,11 +181,12 @@ pub fn encode_to_string(sourcemap: &SourceMap<'_>) -> String {
             }
         });
     total_string_bytes += sc_bytes;
-    let sc_count = if has_source_contents { sourcemap.source_contents.len() } else { 0 };
+    let emit_source_contents = has_source_contents || !options.omit_null_sources_content;
+    let sc_count = if emit_source_contents { sourcemap.source_contents.len() } else { 0 };
 
     // Calculate total capacity needed
     max_segments += 9 + 13; // "names":[ + ],"sources":[
-    if has_source_contents {
+    if emit_source_contents {
         max_segments += 20; // ],"sourcesContent":[
     }
     max_segments += 6 * total_string_bytes; // worst-case escaping (* 6), \0 -> \\u0000
@@ -96,16 +200,21 @@ pub fn encode_to_string(sourcemap: &SourceMap<'_>) -> String {
 
     // Optional ],"x_google_ignoreList":[
     if let Some(x_google_ignore_list) = &sourcemap.x_google_ignore_list {
-        max_segments += 25; // ],"x_google_ignoreList":[
+        max_segments += 25; // ],"x_google_ignoreList":[ (or the shorter ],"ignoreList":[)
 
-        let ig_count = x_google_ignore_list.len();
-        // guess 10 digits per item, 100_000_000 maximum per element
-        max_segments += 10 * ig_count;
+        // Exact digits of every index, plus the commas between them.
+        max_segments += x_google_ignore_list.iter().map(|&id| decimal_len(id)).sum::<usize>();
+        max_segments += x_google_ignore_list.len().saturating_sub(1);
     }
 
     // ],"mappings":"
     max_segments += 14;
     max_segments += estimate_mappings_length(sourcemap);
+    let padding_lines = options.total_generated_lines.map_or(0, |total| {
+        let covered = sourcemap.tokens.last().map_or(1, |token| token.dst_line + 1);
+        total.saturating_sub(covered) as usize
+    });
+    max_segments += padding_lines;
 
     // Optional ,"debugId":<escaped>
     if let Some(debug_id) = sourcemap.get_debug_id() {
@@ -116,48 +225,75 @@ pub fn encode_to_string(sourcemap: &SourceMap<'_>) -> String {
     max_segments += 2;
     let mut contents = PreAllocatedString::new(max_segments);
 
-    contents.push("{\"version\":3,");
-    if let Some(file) = sourcemap.get_file() {
-        contents.push("\"file\":");
-        escape_into(file, contents.as_mut_vec());
-        contents.push(",");
-    }
-
-    if let Some(source_root) = sourcemap.get_source_root() {
-        contents.push("\"sourceRoot\":");
-        escape_into(source_root, contents.as_mut_vec());
-        contents.push(",");
-    }
-
-    contents.push("\"names\":[");
-    contents.push_list(sourcemap.names.iter(), |s, out| escape_into(&**s, out));
-
-    contents.push("],\"sources\":[");
-    contents.push_list(sourcemap.sources.iter(), |s, out| escape_into(&**s, out));
-
-    if has_source_contents {
-        let source_contents = &sourcemap.source_contents;
-        contents.push("],\"sourcesContent\":[");
-        contents.push_list(source_contents.iter(), |v, output| match v {
-            Some(s) => escape_into(&**s, output),
-            None => output.extend_from_slice(b"null"),
-        });
-    }
-
-    if let Some(x_google_ignore_list) = &sourcemap.x_google_ignore_list {
-        contents.push("],\"x_google_ignoreList\":[");
-        contents.push_list(x_google_ignore_list.iter(), |s, output| {
-            output.extend_from_slice(s.to_string().as_bytes());
-        });
-    }
-
-    contents.push("],\"mappings\":\"");
-    serialize_sourcemap_mappings(sourcemap, &mut contents);
-    contents.push("\"");
-
-    if let Some(debug_id) = sourcemap.get_debug_id() {
-        contents.push(",\"debugId\":");
-        escape_into(debug_id, contents.as_mut_vec());
+    // Every field after `version` starts with its own `,`, so any order needs the same capacity.
+    contents.push("{\"version\":3");
+    for field in options.field_order.fields() {
+        match field {
+            Field::File => {
+                if let Some(file) = sourcemap.get_file() {
+                    contents.push(",\"file\":");
+                    escape_into(file, contents.as_mut_vec());
+                }
+            }
+            Field::SourceRoot => {
+                if let Some(source_root) = sourcemap.get_source_root() {
+                    contents.push(",\"sourceRoot\":");
+                    escape_into(source_root, contents.as_mut_vec());
+                }
+            }
+            Field::Names => {
+                contents.push(",\"names\":[");
+                contents.push_list(sourcemap.names.iter(), |s, out| escape_into(&**s, out));
+                contents.push("]");
+            }
+            Field::Sources => {
+                contents.push(",\"sources\":[");
+                contents.push_list(sourcemap.sources.iter(), |s, out| escape_into(&**s, out));
+                contents.push("]");
+            }
+            Field::SourcesContent => {
+                if emit_source_contents {
+                    let source_contents = &sourcemap.source_contents;
+                    contents.push(",\"sourcesContent\":[");
+                    contents.push_list(source_contents.iter(), |v, output| match v {
+                        Some(s) => escape_into(&**s, output),
+                        None => output.extend_from_slice(b"null"),
+                    });
+                    contents.push("]");
+                }
+            }
+            Field::IgnoreList => {
+                if let Some(x_google_ignore_list) = &sourcemap.x_google_ignore_list {
+                    contents.push(",\"");
+                    contents.push(options.ignore_list_key.as_str());
+                    contents.push("\":[");
+                    contents.push_list(x_google_ignore_list.iter(), |s, output| {
+                        output.extend_from_slice(s.to_string().as_bytes());
+                    });
+                    contents.push("]");
+                }
+            }
+            Field::Mappings => {
+                contents.push(",\"mappings\":\"");
+                if options.sourceless_tokens == SourcelessTokens::Keep {
+                    serialize_sourcemap_mappings(sourcemap, &mut contents);
+                } else {
+                    let tokens = sourced_tokens(&sourcemap.tokens, options.sourceless_tokens);
+                    let chunk = TokenChunk::new(0, tokens.len() as u32, 0, 0, 0, 0, 0, 0);
+                    serialize_mappings(&tokens, &chunk, &mut contents);
+                }
+                for _ in 0..padding_lines {
+                    contents.push(";");
+                }
+                contents.push("\"");
+            }
+            Field::DebugId => {
+                if let Some(debug_id) = sourcemap.get_debug_id() {
+                    contents.push(",\"debugId\":");
+                    escape_into(debug_id, contents.as_mut_vec());
+                }
+            }
+        }
     }
 
     contents.push("}");
@@ -168,6 +304,11 @@ pub fn encode_to_string(sourcemap: &SourceMap<'_>) -> String {
     contents.consume()
 }
 
+/// Number of decimal digits of `n`.
+fn decimal_len(n: u32) -> usize {
+    n.checked_ilog10().map_or(1, |log| log as usize + 1)
+}
+
 fn estimate_mappings_length(sourcemap: &SourceMap<'_>) -> usize {
     sourcemap
         .token_chunks
@@ -199,24 +340,95 @@ fn serialize_sourcemap_mappings(sm: &SourceMap<'_>, output: &mut String) {
     }
 }
 
+/// Apply `policy` to the tokens without a source. Dropping or filling tokens never grows the
+/// encoding past `estimate_mappings_length`, which budgets every token at full size.
+fn sourced_tokens(tokens: &[Token], policy: SourcelessTokens) -> Vec<Token> {
+    let mut last_source = None;
+    tokens
+        .iter()
+        .filter_map(|token| {
+            if token.get_source_id().is_some() {
+                last_source = Some(*token);
+                return Some(*token);
+            }
+            match policy {
+                SourcelessTokens::Keep => Some(*token),
+                SourcelessTokens::Skip => None,
+                SourcelessTokens::InheritPrevious => last_source.map(|source| {
+                    Token::new(
+                        token.dst_line,
+                        token.dst_col,
+                        source.src_line,
+                        source.src_col,
+                        source.get_source_id(),
+                        None,
+                    )
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Incremental encoder for the `mappings` field.
+///
+/// Produces the same output as [`SourceMap::to_json_string`], but one token at a time, so
+/// callers generating code can stream mappings without first collecting a [`SourceMap`].
+/// Tokens must be pushed sorted by generated position.
+#[derive(Debug, Default)]
+pub struct MappingsEncoder {
+    state: TokenChunk,
+    need_comma: bool,
+}
+
+impl MappingsEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the VLQ segment for `token` to `out`, relative to the previously pushed token.
+    pub fn push_token(&mut self, token: Token, out: &mut String) {
+        serialize_tokens(std::slice::from_ref(&token), &mut self.state, self.need_comma, out);
+        self.need_comma = true;
+        self.state.end += 1;
+    }
+
+    /// Finish encoding, returning the [`TokenChunk`] for the pushed tokens: `start..end` spans
+    /// them, and the `prev_*` fields hold the state after the last one, which is the baseline for
+    /// encoding the tokens that follow in a separate chunk.
+    pub fn finish(self) -> TokenChunk {
+        self.state
+    }
+}
+
 // Max length of a single VLQ encoding
 const MAX_VLQ_BYTES: usize = 7;
 
 fn serialize_mappings(tokens: &[Token], token_chunk: &TokenChunk, output: &mut String) {
+    let mut state = token_chunk.clone();
+    let tokens = &tokens[token_chunk.start as usize..token_chunk.end as usize];
+    serialize_tokens(tokens, &mut state, token_chunk.start != 0, output);
+}
+
+/// Encode `tokens` relative to the `prev_*` fields of `state`, writing the final
+/// diff state back so a later call can continue where this one stopped.
+#[inline]
+fn serialize_tokens(
+    tokens: &[Token],
+    state: &mut TokenChunk,
+    mut need_comma: bool,
+    output: &mut String,
+) {
     let TokenChunk {
-        start,
-        end,
         mut prev_dst_line,
         mut prev_dst_col,
         mut prev_src_line,
         mut prev_src_col,
         mut prev_name_id,
         mut prev_source_id,
-    } = *token_chunk;
-
-    let mut need_comma = start != 0;
+        ..
+    } = *state;
 
-    for token in &tokens[start as usize..end as usize] {
+    for token in tokens {
         // Max length of a single VLQ encoding is 7 bytes. Max number of calls to `encode_vlq` is 5.
         // Also need 1 byte for each line number difference, or 1 byte if no line num difference.
         // Reserve this amount of capacity in `rv` early, so can skip bounds checks in code below.
@@ -298,6 +510,13 @@ fn serialize_mappings(tokens: &[Token], token_chunk: &TokenChunk, output: &mut S
 
         need_comma = true;
     }
+
+    state.prev_dst_line = prev_dst_line;
+    state.prev_dst_col = prev_dst_col;
+    state.prev_src_line = prev_src_line;
+    state.prev_src_col = prev_src_col;
+    state.prev_name_id = prev_name_id;
+    state.prev_source_id = prev_source_id;
 }
 
 /// Transform the diff `a - b` into its VLQ integer representation: the sign
@@ -377,7 +596,8 @@ static B64_CHARS: Aligned64 =
 /// # SAFETY
 /// Caller must ensure at least 7 bytes spare capacity in `out`,
 /// as this function does not perform any bounds checks.
-unsafe fn encode_vlq(out: &mut String, mut num: u64) {
+/// (Up to 13 bytes for a `num` beyond the `vlq_value` range, see [`crate::vlq::encode_vlq`].)
+pub(crate) unsafe fn encode_vlq(out: &mut String, mut num: u64) {
     unsafe {
         // Breaking out of loop early when have reached last char (rather than conditionally adding
         // 32 for last char within the loop) removes 3 instructions from the loop.
@@ -587,6 +807,25 @@ mod tests {
         );
     }
 
+    /// No SIMD escaping on `wasm32`, so this covers the scalar fallback.
+    #[cfg(target_arch = "wasm32")]
+    #[test]
+    fn to_json_string_on_wasm32() {
+        let sm = SourceMap::new(
+            Some("out.js".into()),
+            vec!["\"quoted\"".into()],
+            None,
+            vec!["a\\b.js".into()],
+            vec![Some("line 1\nline 2\t\0 👀".into())],
+            vec![Token::new(0, 0, 0, 0, Some(0), Some(0))].into_boxed_slice(),
+            None,
+        );
+        assert_eq!(
+            sm.to_json_string(),
+            r#"{"version":3,"file":"out.js","names":["\"quoted\""],"sources":["a\\b.js"],"sourcesContent":["line 1\nline 2\t\u0000 👀"],"mappings":"AAAAA"}"#
+        );
+    }
+
     #[test]
     fn vlq_encode_diff() {
         // Most important tests here are that with maximum values, `encode_vlq` pushes maximum of 7 bytes.
@@ -766,7 +1005,7 @@ mod tests {
         );
 
         // Both encoders must round-trip the same tokens despite the realloc.
-        for encoded in [sm.to_json_string(), encode_to_string(&sm)] {
+        for encoded in [sm.to_json_string(), encode_to_string(&sm, &EncodeOptions::default())] {
             let reparsed = SourceMap::from_json_string(&encoded).unwrap();
             assert!(sm.get_tokens().eq(reparsed.get_tokens()));
         }
@@ -836,4 +1075,195 @@ mod tests {
         assert!(sm.get_tokens().eq(reparsed.get_tokens()));
         assert!(reparsed.get_tokens().all(|token| token.get_source_id().is_none()));
     }
+
+    #[test]
+    fn encode_null_sources_content_option() {
+        let sm = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec!["a.js".into(), "b.js".into()],
+            vec![None, None],
+            vec![].into_boxed_slice(),
+            None,
+        );
+        let omitted = r#"{"version":3,"names":[],"sources":["a.js","b.js"],"mappings":""}"#;
+        assert_eq!(sm.to_json_string(), omitted);
+        assert_eq!(sm.to_json_string_with(&EncodeOptions::default()), omitted);
+        let options =
+            EncodeOptions { omit_null_sources_content: false, ..EncodeOptions::default() };
+        assert_eq!(
+            sm.to_json_string_with(&options),
+            r#"{"version":3,"names":[],"sources":["a.js","b.js"],"sourcesContent":[null,null],"mappings":""}"#
+        );
+    }
+
+    #[test]
+    fn encode_large_ignore_list_indices() {
+        // Indices of every width up to `u32::MAX`; the capacity estimate must cover all digits and
+        // commas (checked by the `debug_assert!` at the end of `encode_to_string`).
+        let ignore_list: Vec<u32> =
+            (0..10).map(|digits| 10u32.pow(digits)).chain([0, 9_999, 10_000, u32::MAX]).collect();
+        let mut sm = SourceMap::default();
+        sm.set_x_google_ignore_list(ignore_list.clone());
+        let json = sm.to_json_string();
+        let expected = ignore_list.iter().map(ToString::to_string).collect::<Vec<_>>().join(",");
+        assert!(json.contains(&format!(r#""x_google_ignoreList":[{expected}]"#)), "{json}");
+    }
+
+    #[test]
+    fn decimal_len() {
+        assert_eq!(super::decimal_len(0), 1);
+        assert_eq!(super::decimal_len(9), 1);
+        assert_eq!(super::decimal_len(10), 2);
+        assert_eq!(super::decimal_len(9_999), 4);
+        assert_eq!(super::decimal_len(10_000), 5);
+        assert_eq!(super::decimal_len(u32::MAX), 10);
+    }
+
+    #[test]
+    fn encode_escape_quote_and_backslash_in_source_root() {
+        let sm = SourceMap::new(
+            Some(r#"out"\.js"#.into()),
+            vec![],
+            Some(r#"a"b\c"#.into()),
+            vec![],
+            vec![],
+            vec![].into_boxed_slice(),
+            None,
+        );
+        let json = sm.to_json_string();
+        assert!(json.contains(r#""sourceRoot":"a\"b\\c""#), "{json}");
+        let roundtripped = SourceMap::from_json_string(&json).unwrap();
+        assert_eq!(roundtripped.get_source_root(), Some(r#"a"b\c"#));
+        assert_eq!(roundtripped.get_file(), Some(r#"out"\.js"#));
+    }
+
+    #[test]
+    fn encode_ignore_list_key() {
+        let mut sm = SourceMap::default();
+        sm.set_sources(["a.js", "b.js"]);
+        sm.set_x_google_ignore_list(vec![1]);
+
+        assert!(sm.to_json_string().contains(r#""x_google_ignoreList":[1]"#));
+
+        let options = EncodeOptions {
+            ignore_list_key: IgnoreListKey::IgnoreList,
+            ..EncodeOptions::default()
+        };
+        let json = sm.to_json_string_with(&options);
+        assert!(json.contains(r#""ignoreList":[1]"#), "{json}");
+        assert!(!json.contains("x_google"), "{json}");
+        let decoded = SourceMap::from_json_string(&json).unwrap();
+        assert_eq!(decoded.get_x_google_ignore_list(), Some(&[1][..]));
+    }
+
+    #[test]
+    fn mappings_encoder_matches_encode() {
+        let input = r#"{
+            "version": 3,
+            "names": ["a"],
+            "sources": ["x.js", "y.js"],
+            "mappings": "AAAAA,CAAC;;ECCE,GAAGA;A"
+        }"#;
+        let sm = SourceMap::from_json_string(input).unwrap();
+
+        let mut encoder = MappingsEncoder::new();
+        let mut out = String::new();
+        for token in sm.get_tokens() {
+            encoder.push_token(token, &mut out);
+        }
+        let chunk = encoder.finish();
+
+        let json: serde_json::Value = serde_json::from_str(&sm.to_json_string()).unwrap();
+        assert_eq!(out, json["mappings"]);
+        let last = sm.get_token(sm.token_count() as u32 - 1).unwrap();
+        assert_eq!((chunk.start, chunk.end), (0, sm.token_count() as u32));
+        assert_eq!(
+            (chunk.prev_dst_line, chunk.prev_dst_col, chunk.prev_src_line, chunk.prev_src_col),
+            (last.get_dst_line(), last.get_dst_col(), last.get_src_line(), last.get_src_col())
+        );
+        assert_eq!((chunk.prev_source_id, chunk.prev_name_id), (1, 0));
+    }
+
+    #[test]
+    fn encode_total_generated_lines() {
+        let sm = SourceMap::builder()
+            .sources(["a.js"])
+            .tokens(vec![
+                Token::new(0, 0, 0, 0, Some(0), None),
+                Token::new(1, 2, 1, 0, Some(0), None),
+            ])
+            .build();
+        let mappings = |total_generated_lines| {
+            let options = EncodeOptions { total_generated_lines, ..EncodeOptions::default() };
+            let json: serde_json::Value =
+                serde_json::from_str(&sm.to_json_string_with(&options)).unwrap();
+            json["mappings"].as_str().unwrap().to_string()
+        };
+        assert_eq!(mappings(None), "AAAA;EACA");
+        assert_eq!(mappings(Some(4)), "AAAA;EACA;;");
+        // Fewer lines than are mapped leaves the mappings untouched.
+        assert_eq!(mappings(Some(1)), "AAAA;EACA");
+
+        let empty = SourceMap::default().to_json_string_with(&EncodeOptions {
+            total_generated_lines: Some(3),
+            ..EncodeOptions::default()
+        });
+        assert!(empty.contains(r#""mappings":";;""#), "{empty}");
+    }
+
+    #[test]
+    fn encode_sourceless_tokens() {
+        let sm = SourceMap::builder()
+            .sources(["a.js"])
+            .names(["n"])
+            .tokens(vec![
+                Token::new(0, 0, 0, 0, None, None),
+                Token::new(0, 2, 1, 3, Some(0), Some(0)),
+                Token::new(0, 5, 0, 0, None, None),
+                Token::new(1, 0, 2, 0, Some(0), None),
+            ])
+            .build();
+        let encode = |sourceless_tokens| {
+            let options = EncodeOptions { sourceless_tokens, ..EncodeOptions::default() };
+            SourceMap::from_json_string(&sm.to_json_string_with(&options))
+                .unwrap()
+                .get_tokens()
+                .collect::<Vec<_>>()
+        };
+        let keep =
+            EncodeOptions { sourceless_tokens: SourcelessTokens::Keep, ..EncodeOptions::default() };
+        assert_eq!(sm.to_json_string_with(&keep), sm.to_json_string());
+        assert_eq!(
+            encode(SourcelessTokens::Skip),
+            [Token::new(0, 2, 1, 3, Some(0), Some(0)), Token::new(1, 0, 2, 0, Some(0), None)]
+        );
+        assert_eq!(
+            encode(SourcelessTokens::InheritPrevious),
+            [
+                Token::new(0, 2, 1, 3, Some(0), Some(0)),
+                Token::new(0, 5, 1, 3, Some(0), None),
+                Token::new(1, 0, 2, 0, Some(0), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_field_order() {
+        let input = r#"{"version":3,"file":"out.js","sourceRoot":"src/","names":["x"],"sources":["a.js"],"sourcesContent":["a"],"x_google_ignoreList":[0],"mappings":"AAAAA","debugId":"56431d54-c0a6-451d-8ea2-ba5de5d8ca2e"}"#;
+        let sm = SourceMap::from_json_string(input).unwrap();
+        assert_eq!(sm.to_json_string(), input);
+
+        let options = EncodeOptions { field_order: FieldOrder::Spec, ..EncodeOptions::default() };
+        let json = sm.to_json_string_with(&options);
+        assert_eq!(
+            json,
+            r#"{"version":3,"file":"out.js","sources":["a.js"],"sourceRoot":"src/","sourcesContent":["a"],"names":["x"],"mappings":"AAAAA","x_google_ignoreList":[0],"debugId":"56431d54-c0a6-451d-8ea2-ba5de5d8ca2e"}"#
+        );
+        assert_eq!(SourceMap::from_json_string(&json).unwrap(), sm);
+
+        let empty = SourceMap::default().to_json_string_with(&options);
+        assert_eq!(empty, r#"{"version":3,"sources":[],"names":[],"mappings":""}"#);
+    }
 }
@@ -1,6 +1,7 @@
 use std::{error, fmt};
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// a VLQ string was malformed and data was left over
     VlqLeftover,
@@ -16,7 +17,49 @@ pub enum Error {
     BadSourceReference(u32),
     /// a reference to a non existing name was encountered
     BadNameReference(u32),
+    /// `sourcesContent` had this length, which differs from `sources`
+    BadSourcesContentLength(u32),
+    /// the token at this index is positioned before the previous token
+    UnsortedToken(u32),
+    /// a `debugId` was not a UUID
+    BadDebugId(String),
+    /// the `version` field was this value instead of 3
+    UnsupportedVersion(u32),
 }
+
+/// Coarse classification of an [`Error`], see [`Error::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input was not valid source map JSON, including a mismatched `sourcesContent` length
+    /// or a malformed `debugId`.
+    Json,
+    /// The `mappings` VLQ data was malformed, or tokens are out of the order it requires.
+    Vlq,
+    /// A mapping referenced a source or name that does not exist.
+    BadReference,
+    /// The source map declares a `version` other than 3.
+    Version,
+}
+
+impl Error {
+    /// Classify this error, so callers can branch without matching every variant.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::BadJson(_) | Error::BadSourcesContentLength(_) | Error::BadDebugId(_) => {
+                ErrorKind::Json
+            }
+            Error::VlqLeftover
+            | Error::VlqNoValues
+            | Error::VlqOverflow
+            | Error::BadSegmentSize(_)
+            | Error::UnsortedToken(_) => ErrorKind::Vlq,
+            Error::BadSourceReference(_) | Error::BadNameReference(_) => ErrorKind::BadReference,
+            Error::UnsupportedVersion(_) => ErrorKind::Version,
+        }
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -33,6 +76,16 @@ impl fmt::Display for Error {
             Error::BadNameReference(idx) => {
                 write!(f, "Reference to non-existing name at position {idx}")
             }
+            Error::BadSourcesContentLength(len) => {
+                write!(f, "sourcesContent of length {len} does not match sources")
+            }
+            Error::UnsortedToken(idx) => {
+                write!(f, "Token at position {idx} is positioned before the previous token")
+            }
+            Error::BadDebugId(id) => write!(f, "debugId {id:?} is not a UUID"),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "unsupported source map version: {version}")
+            }
         }
     }
 }
@@ -89,6 +142,16 @@ mod tests {
             Error::BadNameReference(9).to_string(),
             "Reference to non-existing name at position 9"
         );
+        assert_eq!(
+            Error::BadSourcesContentLength(2).to_string(),
+            "sourcesContent of length 2 does not match sources"
+        );
+        assert_eq!(
+            Error::UnsortedToken(4).to_string(),
+            "Token at position 4 is positioned before the previous token"
+        );
+        assert_eq!(Error::BadDebugId("x".into()).to_string(), r#"debugId "x" is not a UUID"#);
+        assert_eq!(Error::UnsupportedVersion(2).to_string(), "unsupported source map version: 2");
     }
 
     #[test]
@@ -104,4 +167,17 @@ mod tests {
         let err: Error = bad_json_error().into();
         assert!(matches!(err, Error::BadJson(_)));
     }
+
+    #[test]
+    fn kind() {
+        assert_eq!(Error::BadSegmentSize(2).kind(), ErrorKind::Vlq);
+        assert_eq!(Error::VlqLeftover.kind(), ErrorKind::Vlq);
+        assert_eq!(Error::BadNameReference(0).kind(), ErrorKind::BadReference);
+        assert_eq!(Error::BadJson(bad_json_error()).kind(), ErrorKind::Json);
+
+        let version = crate::SourceMap::from_json_string(
+            r#"{"version":2,"sources":[],"names":[],"mappings":""}"#,
+        );
+        assert_eq!(version.unwrap_err().kind(), ErrorKind::Version);
+    }
 }
@@ -1,21 +1,32 @@
 mod concat_sourcemap_builder;
+mod data_url;
+mod debug_id;
 mod decode;
 mod encode;
 mod error;
 mod owned_sourcemap;
 mod sourcemap;
 mod sourcemap_builder;
+#[cfg(feature = "sourcemap-compat")]
+mod sourcemap_compat;
 mod sourcemap_visualizer;
 mod token;
+mod validate;
+pub mod vlq;
 
 #[cfg(feature = "napi")]
 pub mod napi;
 
 pub use concat_sourcemap_builder::ConcatSourceMapBuilder;
-pub use decode::JSONSourceMap;
-pub use error::Error;
+pub use data_url::DataUrlError;
+pub use decode::{DecodeOptions, DecodeWarning, JSONSourceMap};
+pub use encode::{EncodeOptions, FieldOrder, IgnoreListKey, MappingsEncoder, SourcelessTokens};
+pub use error::{Error, ErrorKind};
 pub use owned_sourcemap::OwnedSourceMap;
-pub use sourcemap::{SourceMap, SourceMapParts};
+pub use sourcemap::{
+    ReverseLookupTable, SourceMap, SourceMapFieldBuilder, SourceMapParts, SparseLookupTable,
+};
 pub use sourcemap_builder::SourceMapBuilder;
 pub use sourcemap_visualizer::SourcemapVisualizer;
-pub use token::{SourceViewToken, Token, TokenChunk};
+pub use token::{OwnedSourceViewToken, SourceViewToken, Token, TokenChunk, TokenEnd};
+pub use validate::ValidationIssue;
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use napi_derive::napi;
 
 // Aligned with Rollup's sourcemap input.
@@ -54,6 +56,144 @@ impl From<crate::OwnedSourceMap> for SourceMap {
     }
 }
 
+/// An original position, as returned by [`lookup_token`] and [`ParsedSourceMap::lookup_token`].
+#[napi(object)]
+pub struct OriginalToken {
+    pub source: Option<String>,
+    pub line: u32,
+    pub column: u32,
+    pub name: Option<String>,
+}
+
+impl From<crate::SourceViewToken<'_, '_>> for OriginalToken {
+    fn from(token: crate::SourceViewToken<'_, '_>) -> Self {
+        Self {
+            source: token.get_source().map(ToString::to_string),
+            line: token.get_src_line(),
+            column: token.get_src_col(),
+            name: token.get_name().map(ToString::to_string),
+        }
+    }
+}
+
+fn parse(source_map: &str) -> napi::Result<crate::SourceMap<'_>> {
+    crate::SourceMap::from_json_string(source_map)
+        .map_err(|err| napi::Error::from_reason(err.to_string()))
+}
+
+/// Look up the original position of the generated `line`/`column` (0-based) in the
+/// `source_map` JSON. For repeated lookups, parse once into a [`ParsedSourceMap`].
+#[napi]
+pub fn lookup_token(
+    source_map: String,
+    line: u32,
+    column: u32,
+) -> napi::Result<Option<OriginalToken>> {
+    let source_map = parse(&source_map)?;
+    let lookup_table = source_map.generate_lookup_table();
+    Ok(source_map.lookup_source_view_token(&lookup_table, line, column).map(OriginalToken::from))
+}
+
+/// Compose two source maps given as JSON: `source_map` maps the output to an intermediate file,
+/// which `original` maps back to the original sources. Returns the JSON of a map from the output
+/// straight to the original sources.
+#[napi]
+pub fn remap(source_map: String, original: String) -> napi::Result<String> {
+    let original = parse(&original)?;
+    let lookup_table = original.generate_lookup_table();
+    let trace = |line, column| original.lookup_source_view_token(&lookup_table, line, column);
+    Ok(compose(&parse(&source_map)?, trace).to_json_string())
+}
+
+/// A source map parsed once, with its lookup table, for repeated lookups from JavaScript.
+#[napi]
+pub struct ParsedSourceMap {
+    source_map: crate::OwnedSourceMap,
+    /// Index of the first token on each generated line, plus the token count as the last entry,
+    /// so line `l`'s tokens are `line_starts[l]..line_starts[l + 1]`.
+    line_starts: Vec<u32>,
+}
+
+#[napi]
+impl ParsedSourceMap {
+    #[napi(constructor)]
+    pub fn new(source_map: String) -> napi::Result<Self> {
+        let source_map = crate::OwnedSourceMap::from_json_string(&source_map)
+            .map_err(|err| napi::Error::from_reason(err.to_string()))?;
+        let mut line_starts = vec![];
+        for (index, token) in source_map.get_tokens().enumerate() {
+            while line_starts.len() <= token.get_dst_line() as usize {
+                line_starts.push(index as u32);
+            }
+        }
+        line_starts.push(source_map.as_source_map().tokens.len() as u32);
+        Ok(Self { source_map, line_starts })
+    }
+
+    /// Look up the original position of the generated `line`/`column` (0-based).
+    #[napi]
+    pub fn lookup_token(&self, line: u32, column: u32) -> Option<OriginalToken> {
+        self.trace(line, column).map(OriginalToken::from)
+    }
+
+    /// Compose with `original`, which maps this map's sources back to the original sources.
+    /// Returns the JSON of a map from this map's output straight to the original sources.
+    #[napi]
+    pub fn remap(&self, original: &ParsedSourceMap) -> String {
+        let trace = |line, column| original.trace(line, column);
+        compose(self.source_map.as_source_map(), trace).to_json_string()
+    }
+
+    fn trace(&self, line: u32, column: u32) -> Option<crate::SourceViewToken<'_, 'static>> {
+        let start = *self.line_starts.get(line as usize)?;
+        let end = *self.line_starts.get(line as usize + 1)?;
+        let tokens = &self.source_map.as_source_map().tokens[start as usize..end as usize];
+        let index =
+            crate::sourcemap::greatest_lower_bound_index(tokens, &column, |token| token.dst_col)?;
+        self.source_map.get_source_view_token(start + index as u32)
+    }
+}
+
+/// Trace every token of `source_map` through `trace`, which looks up a position in the map of
+/// the intermediate file, dropping the tokens it does not map. A token's own name wins over the
+/// one found by `trace`.
+fn compose<'a, 'original: 'a>(
+    source_map: &'a crate::SourceMap<'_>,
+    trace: impl Fn(u32, u32) -> Option<crate::SourceViewToken<'a, 'original>>,
+) -> crate::SourceMap<'a> {
+    let mut builder = crate::SourceMapBuilder::default();
+    if let Some(file) = source_map.get_file() {
+        builder.set_file(file);
+    }
+    // The builder records every source with content; keep `original`'s `None`s instead.
+    let mut source_contents = vec![];
+    for token in source_map.get_tokens() {
+        if token.get_source_id().is_none() {
+            continue;
+        }
+        let Some(traced) = trace(token.get_src_line(), token.get_src_col()) else { continue };
+        let Some(source) = traced.get_source() else { continue };
+        let content = traced.get_source_content();
+        let source_id = builder.add_source_and_content(source, content.unwrap_or_default());
+        if source_id as usize == source_contents.len() {
+            source_contents.push(content);
+        }
+        let name = token.get_name_id().and_then(|id| source_map.get_name(id));
+        let name_id = name.or_else(|| traced.get_name()).map(|name| builder.add_name(name));
+        builder.add_token(
+            token.get_dst_line(),
+            token.get_dst_col(),
+            traced.get_src_line(),
+            traced.get_src_col(),
+            Some(source_id),
+            name_id,
+        );
+    }
+    let mut composed = builder.into_sourcemap();
+    composed.source_contents = source_contents.into_iter().map(|c| c.map(Cow::Borrowed)).collect();
+    composed
+}
+
 #[cfg(test)]
 mod tests {
     use super::SourceMap;
@@ -106,4 +246,75 @@ mod tests {
         assert!(napi.sources.is_empty());
         assert_eq!(napi.sources_content, None);
     }
+
+    #[test]
+    fn lookup_token() {
+        let json = r#"{"version":3,"names":["x"],"sources":["a.js"],"mappings":"AAAAA,IAAI"}"#;
+        let token = super::lookup_token(json.to_string(), 0, 5).unwrap().unwrap();
+        assert_eq!(token.source.as_deref(), Some("a.js"));
+        assert_eq!((token.line, token.column), (0, 4));
+        assert_eq!(token.name, None);
+
+        let token = super::lookup_token(json.to_string(), 0, 0).unwrap().unwrap();
+        assert_eq!(token.name.as_deref(), Some("x"));
+        assert!(super::lookup_token(json.to_string(), 1, 0).unwrap().is_none());
+        assert!(super::lookup_token("{}".to_string(), 0, 0).is_err());
+    }
+
+    #[test]
+    fn parsed_source_map_lookup_token() {
+        let json =
+            r#"{"version":3,"names":["x"],"sources":["a.js"],"mappings":"AAAAA,IAAI;;AACA"}"#;
+        let parsed = super::ParsedSourceMap::new(json.to_string()).unwrap();
+        for (line, column) in [(0, 0), (0, 5), (1, 0), (2, 0), (2, 3), (3, 0)] {
+            let expected = super::lookup_token(json.to_string(), line, column).unwrap();
+            let token = parsed.lookup_token(line, column);
+            assert_eq!(
+                token.map(|t| (t.source, t.line, t.column, t.name)),
+                expected.map(|t| (t.source, t.line, t.column, t.name)),
+                "{line}:{column}"
+            );
+        }
+        assert_eq!(parsed.lookup_token(2, 0).unwrap().line, 1);
+        assert!(parsed.lookup_token(1, 0).is_none());
+        assert!(super::ParsedSourceMap::new("{}".to_string()).is_err());
+    }
+
+    #[test]
+    fn remap() {
+        // min.js -> bundle.js: columns 0 and 10 map to bundle.js 1:0 and 2:4.
+        let mut builder = crate::SourceMapBuilder::default();
+        builder.set_file("min.js");
+        let bundle = builder.set_source_and_content("bundle.js", "");
+        builder.add_token(0, 0, 1, 0, Some(bundle), None);
+        builder.add_token(0, 10, 2, 4, Some(bundle), None);
+        builder.add_token(0, 20, 9, 0, Some(bundle), None);
+        let minified = builder.into_sourcemap().to_json_string();
+
+        // bundle.js -> a.js (line 1) and b.js (line 2); line 9 is unmapped.
+        let mut builder = crate::SourceMapBuilder::default();
+        let a = builder.add_source_and_content("a.js", "let a");
+        let b = builder.add_source_and_content("b.js", "let b");
+        let name = builder.add_name("b");
+        builder.add_token(1, 0, 0, 0, Some(a), None);
+        builder.add_token(2, 4, 0, 4, Some(b), Some(name));
+        let bundled = builder.into_sourcemap().to_json_string();
+
+        let parsed = super::ParsedSourceMap::new(minified.clone())
+            .unwrap()
+            .remap(&super::ParsedSourceMap::new(bundled.clone()).unwrap());
+        let json = super::remap(minified, bundled).unwrap();
+        assert_eq!(parsed, json);
+        let composed = crate::SourceMap::from_json_string(&json).unwrap();
+        assert_eq!(composed.get_file(), Some("min.js"));
+        assert_eq!(composed.get_sources().collect::<Vec<_>>(), ["a.js", "b.js"]);
+        assert_eq!(
+            composed.get_source_contents().collect::<Vec<_>>(),
+            [Some("let a"), Some("let b")]
+        );
+        assert_eq!(
+            composed.get_source_view_tokens().map(|t| t.to_tuple()).collect::<Vec<_>>(),
+            [(Some("a.js"), 0, 0, None), (Some("b.js"), 0, 4, Some("b"))]
+        );
+    }
 }
@@ -110,6 +110,10 @@ impl OwnedSourceMap {
         self.inner.get_source_root()
     }
 
+    pub fn set_source_root(&mut self, source_root: &str) {
+        self.inner.set_source_root(source_root);
+    }
+
     pub fn get_x_google_ignore_list(&self) -> Option<&[u32]> {
         self.inner.get_x_google_ignore_list()
     }
@@ -186,6 +190,19 @@ impl OwnedSourceMap {
         self.inner.lookup_token(lookup_table, line, col)
     }
 
+    pub fn lookup_token_index(
+        &self,
+        lookup_table: &[&[Token]],
+        line: u32,
+        col: u32,
+    ) -> Option<usize> {
+        self.inner.lookup_token_index(lookup_table, line, col)
+    }
+
+    pub fn tokens_on_line<'t>(&self, lookup_table: &[&'t [Token]], line: u32) -> &'t [Token] {
+        self.inner.tokens_on_line(lookup_table, line)
+    }
+
     pub fn lookup_source_view_token(
         &self,
         lookup_table: &[&[Token]],
@@ -321,6 +338,8 @@ mod tests {
         let sm = OwnedSourceMap::from_json_string(JSON).unwrap();
         let table = sm.generate_lookup_table();
         assert!(sm.lookup_token(&table, 0, 0).is_some());
+        assert_eq!(sm.lookup_token_index(&table, 0, 0), Some(0));
+        assert_eq!(sm.tokens_on_line(&table, 0).len(), 1);
         assert!(sm.lookup_source_view_token(&table, 0, 0).is_some());
     }
 
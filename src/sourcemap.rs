@@ -1,11 +1,16 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt, ops::Range, str::FromStr, sync::OnceLock};
+
+use rustc_hash::{FxBuildHasher, FxHashMap};
 
 use crate::{
-    SourceViewToken,
-    decode::{JSONSourceMap, decode, decode_from_string},
-    encode::{encode, encode_to_string},
-    error::Result,
-    token::{Token, TokenChunk},
+    ConcatSourceMapBuilder, SourceViewToken, debug_id,
+    decode::{
+        DecodeOptions, DecodeWarning, JSONSourceMap, decode, decode_from_slice, decode_from_string,
+        decode_lenient,
+    },
+    encode::{EncodeOptions, MappingsEncoder, encode, encode_to_string},
+    error::{Error, Result},
+    token::{Token, TokenChunk, TokenEnd},
 };
 
 /// A parsed source map.
@@ -16,6 +21,9 @@ use crate::{
 /// input JSON buffer — most strings (those without JSON escapes) are zero-copy
 /// views into that buffer; only escaped strings allocate. For maps built
 /// programmatically via the builder, the lifetime is `'static`.
+///
+/// `SourceMap` only holds plain owned or borrowed data and thread-safe lazily built indexes, so it
+/// is `Send + Sync`: share one across threads by reference or behind an `Arc` directly.
 #[derive(Debug, Clone, Default)]
 pub struct SourceMap<'a> {
     pub(crate) file: Option<Cow<'a, str>>,
@@ -30,6 +38,50 @@ pub struct SourceMap<'a> {
     /// When parsing the source map, developer tools can use this to determine sections of the code that the browser loads and runs that could be automatically ignore-listed.
     pub(crate) x_google_ignore_list: Option<Vec<u32>>,
     pub(crate) debug_id: Option<Cow<'a, str>>,
+    /// End columns parallel to `tokens`, when known; see [`SourceMap::set_token_ends`].
+    pub(crate) token_ends: Option<Box<[TokenEnd]>>,
+    pub(crate) cache: SourceMapCache<'a>,
+}
+
+/// Reverse indexes and per-source line starts derived from a [`SourceMap`]'s other fields, built
+/// on first use.
+///
+/// Mutators that change `names`, `sources` or `source_contents` keep them in sync or reset them.
+#[derive(Clone, Default)]
+pub(crate) struct SourceMapCache<'a> {
+    names: OnceLock<FxHashMap<Cow<'a, str>, u32>>,
+    sources: OnceLock<FxHashMap<Cow<'a, str>, u32>>,
+    /// Indexed by source id, each filled by the first lookup into that source.
+    line_starts: OnceLock<Vec<OnceLock<Vec<u32>>>>,
+}
+
+impl SourceMapCache<'_> {
+    fn reset_names(&mut self) {
+        self.names.take();
+    }
+
+    fn reset_sources(&mut self) {
+        self.sources.take();
+    }
+
+    fn reset_line_starts(&mut self) {
+        self.line_starts.take();
+    }
+}
+
+impl fmt::Debug for SourceMapCache<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SourceMapCache").finish_non_exhaustive()
+    }
+}
+
+/// Map each item to the id of its first occurrence.
+fn first_occurrences<'a>(items: &[Cow<'a, str>]) -> FxHashMap<Cow<'a, str>, u32> {
+    let mut ids = FxHashMap::with_capacity_and_hasher(items.len(), FxBuildHasher);
+    for (id, item) in items.iter().enumerate() {
+        ids.entry(item.clone()).or_insert(id as u32);
+    }
+    ids
 }
 
 impl<'a> SourceMap<'a> {
@@ -52,7 +104,39 @@ impl<'a> SourceMap<'a> {
             token_chunks,
             x_google_ignore_list: None,
             debug_id: None,
+            token_ends: None,
+            cache: SourceMapCache::default(),
+        }
+    }
+
+    /// Same as [`Self::new`], but checks that every token references an existing source and
+    /// name. `new` skips this check for speed.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::BadSourceReference`] or [`Error::BadNameReference`] with the first out-of-range id.
+    pub fn try_new(
+        file: Option<Cow<'a, str>>,
+        names: Vec<Cow<'a, str>>,
+        source_root: Option<Cow<'a, str>>,
+        sources: Vec<Cow<'a, str>>,
+        source_contents: Vec<Option<Cow<'a, str>>>,
+        tokens: Box<[Token]>,
+        token_chunks: Option<Vec<TokenChunk>>,
+    ) -> Result<Self> {
+        for token in &tokens {
+            if let Some(source_id) = token.get_source_id()
+                && source_id as usize >= sources.len()
+            {
+                return Err(Error::BadSourceReference(source_id));
+            }
+            if let Some(name_id) = token.get_name_id()
+                && name_id as usize >= names.len()
+            {
+                return Err(Error::BadNameReference(name_id));
+            }
         }
+        Ok(Self::new(file, names, source_root, sources, source_contents, tokens, token_chunks))
     }
 
     /// Convert the vlq sourcemap to to `SourceMap`.
@@ -72,7 +156,38 @@ impl<'a> SourceMap<'a> {
     ///
     /// The `serde_json` deserialize Error.
     pub fn from_json_string(value: &'a str) -> Result<SourceMap<'a>> {
-        decode_from_string(value)
+        decode_from_string(value, &DecodeOptions::default())
+    }
+
+    /// Same as [`Self::from_json_string`], configured by `options`.
+    ///
+    /// # Errors
+    ///
+    /// The `serde_json` deserialize Error.
+    pub fn from_json_string_with(value: &'a str, options: &DecodeOptions) -> Result<SourceMap<'a>> {
+        decode_from_string(value, options)
+    }
+
+    /// Same as [`Self::from_json_string`], for raw bytes such as a file read with `fs::read`.
+    ///
+    /// Borrows from `value` the same way, so neither the `mappings` string nor the other strings
+    /// are copied; unlike going through [`JSONSourceMap`], which allocates every string including
+    /// `mappings` (often the largest field), or `String::from_utf8` first.
+    ///
+    /// # Errors
+    ///
+    /// The `serde_json` deserialize Error, including for invalid UTF-8.
+    pub fn from_json_slice(value: &'a [u8]) -> Result<SourceMap<'a>> {
+        decode_from_slice(value)
+    }
+
+    /// Decode whatever can be salvaged from `value`, reporting what was wrong instead of failing.
+    ///
+    /// Unlike [`Self::from_json_slice`], never fails: invalid JSON gives an empty map, a malformed
+    /// `mappings` keeps the tokens before the bad segment, overflowing values are clamped as with
+    /// [`DecodeOptions::clamp_overflow`], and bad ignore list entries are dropped.
+    pub fn from_json_bytes_lenient(value: &'a [u8]) -> (SourceMap<'a>, Vec<DecodeWarning>) {
+        decode_lenient(value)
     }
 
     /// Convert `SourceMap` to vlq sourcemap.
@@ -82,7 +197,34 @@ impl<'a> SourceMap<'a> {
 
     /// Convert `SourceMap` to vlq sourcemap string.
     pub fn to_json_string(&self) -> String {
-        encode_to_string(self)
+        encode_to_string(self, &EncodeOptions::default())
+    }
+
+    /// Same as [`Self::to_json_string`], but indented with 2 spaces for human-readable `.map`
+    /// files. Goes through [`JSONSourceMap`] and `serde_json`, so it is slower than the compact
+    /// encoder; `mappings` is still a single string.
+    pub fn to_json_string_pretty(&self) -> String {
+        serde_json::to_string_pretty(&self.to_json()).unwrap()
+    }
+
+    /// Same as [`Self::to_json_string`], configured by `options`.
+    pub fn to_json_string_with(&self, options: &EncodeOptions) -> String {
+        encode_to_string(self, options)
+    }
+
+    /// The `mappings` string for just the generated lines `start_line..end_line`, rebased so
+    /// `start_line` becomes line 0. Source and name ids keep referring to this map's arrays.
+    pub fn encode_mappings_range(&self, start_line: u32, end_line: u32) -> String {
+        let start = self.tokens.partition_point(|token| token.dst_line < start_line);
+        let end = self.tokens.partition_point(|token| token.dst_line < end_line);
+        let mut encoder = MappingsEncoder::new();
+        let mut mappings = String::new();
+        for token in self.tokens.get(start..end).unwrap_or_default() {
+            let mut token = *token;
+            token.dst_line -= start_line;
+            encoder.push_token(token, &mut mappings);
+        }
+        mappings
     }
 
     /// Convert `SourceMap` to vlq sourcemap data url.
@@ -91,6 +233,18 @@ impl<'a> SourceMap<'a> {
         format!("data:application/json;charset=utf-8;base64,{base_64_str}")
     }
 
+    /// The `//# sourceMappingURL=` comment embedding this map as a data URL, to append on its own
+    /// line at the end of the generated code. No trailing newline is included.
+    pub fn inline_comment(&self) -> String {
+        Self::external_comment(&self.to_data_url())
+    }
+
+    /// The `//# sourceMappingURL=` comment pointing at the map file `url`, to append on its own
+    /// line at the end of the generated code. No trailing newline is included.
+    pub fn external_comment(url: &str) -> String {
+        format!("//# sourceMappingURL={url}")
+    }
+
     /// Detach this `SourceMap` from its input buffer by allocating owned
     /// copies of any borrowed strings. Use this when the resulting map
     /// needs to outlive the JSON input it was parsed from, or when the
@@ -114,15 +268,29 @@ impl<'a> SourceMap<'a> {
             token_chunks: self.token_chunks,
             x_google_ignore_list: self.x_google_ignore_list,
             debug_id: self.debug_id.map(|c| Cow::Owned(c.into_owned())),
+            token_ends: self.token_ends,
+            cache: SourceMapCache::default(),
         }
     }
 
+    /// Move this map behind an [`Arc`](std::sync::Arc), for cheap read-only handles across
+    /// threads.
+    ///
+    /// `Clone` on a `SourceMap` copies the token array and every owned string; cloning the `Arc`
+    /// only bumps a reference count. All read APIs take `&self`, so they work through the `Arc`.
+    pub fn shared(self) -> std::sync::Arc<Self> {
+        std::sync::Arc::new(self)
+    }
+
     /// Decompose this `SourceMap` into its constituent owned parts.
     ///
     /// Useful for downstream code that wants to consume the map (e.g.
     /// transform tokens, swap in a different `file` field) without
     /// re-cloning every name/source string via accessors. Pair with
     /// [`SourceMap::from_parts`] (or `From`) to reassemble.
+    ///
+    /// End columns set with [`SourceMap::set_token_ends`] are not part of the parts and are
+    /// dropped.
     pub fn into_parts(self) -> SourceMapParts<'a> {
         SourceMapParts {
             file: self.file,
@@ -149,9 +317,29 @@ impl<'a> SourceMap<'a> {
             token_chunks: parts.token_chunks,
             x_google_ignore_list: parts.x_google_ignore_list,
             debug_id: parts.debug_id,
+            token_ends: None,
+            cache: SourceMapCache::default(),
         }
     }
 
+    /// Start constructing a `SourceMap` field by field, as a self-documenting alternative to
+    /// [`SourceMap::new`]'s positional arguments. Unlike [`crate::SourceMapBuilder`] nothing is
+    /// deduplicated: each setter stores its value as given.
+    ///
+    /// ```
+    /// use oxc_sourcemap::{SourceMap, Token};
+    ///
+    /// let sm = SourceMap::builder()
+    ///     .file("out.js")
+    ///     .sources(["a.js"])
+    ///     .tokens(vec![Token::new(0, 0, 0, 0, Some(0), None)])
+    ///     .build();
+    /// assert_eq!(sm.get_source(0), Some("a.js"));
+    /// ```
+    pub fn builder() -> SourceMapFieldBuilder<'a> {
+        SourceMapFieldBuilder::default()
+    }
+
     pub fn get_file(&self) -> Option<&str> {
         self.file.as_deref()
     }
@@ -160,10 +348,32 @@ impl<'a> SourceMap<'a> {
         self.file = Some(Cow::Owned(file.to_owned()));
     }
 
+    /// Remove `file`, so it is omitted from the encoded JSON.
+    pub fn clear_file(&mut self) {
+        self.file = None;
+    }
+
     pub fn get_source_root(&self) -> Option<&str> {
         self.source_root.as_deref()
     }
 
+    /// Set `sourceRoot`, normalized to end in exactly one `/` (an empty root stays empty), so
+    /// `"a"`, `"a/"` and `"a//"` all resolve `x.js` to `a/x.js` in
+    /// [`get_resolved_source`](Self::get_resolved_source).
+    pub fn set_source_root(&mut self, source_root: &str) {
+        let trimmed = source_root.trim_end_matches('/');
+        self.source_root = Some(Cow::Owned(if source_root.is_empty() {
+            String::new()
+        } else {
+            format!("{trimmed}/")
+        }));
+    }
+
+    /// Remove `sourceRoot`. Sources are then resolved as-is.
+    pub fn clear_source_root(&mut self) {
+        self.source_root = None;
+    }
+
     pub fn get_x_google_ignore_list(&self) -> Option<&[u32]> {
         self.x_google_ignore_list.as_deref()
     }
@@ -173,10 +383,56 @@ impl<'a> SourceMap<'a> {
         self.x_google_ignore_list = Some(x_google_ignore_list);
     }
 
+    /// Whether `x_google_ignoreList` marks the source at `source_id` as third-party.
+    pub fn is_source_ignored(&self, source_id: u32) -> bool {
+        self.x_google_ignore_list
+            .as_ref()
+            .is_some_and(|ignore_list| ignore_list.contains(&source_id))
+    }
+
+    /// Set `debugId` as given; see [`Self::try_set_debug_id`] to reject malformed ids.
     pub fn set_debug_id(&mut self, debug_id: &str) {
         self.debug_id = Some(Cow::Owned(debug_id.to_owned()));
     }
 
+    /// Set `debugId`, checking that it is a UUID such as `56431d54-c0a6-451d-8ea2-ba5de5d8ca2e`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::BadDebugId`] when `debug_id` is not a hyphenated UUID; `debugId` is left as is.
+    pub fn try_set_debug_id(&mut self, debug_id: &str) -> Result<()> {
+        if !debug_id::is_valid_uuid(debug_id) {
+            return Err(Error::BadDebugId(debug_id.to_owned()));
+        }
+        self.set_debug_id(debug_id);
+        Ok(())
+    }
+
+    /// Remove `debugId`.
+    pub fn clear_debug_id(&mut self) {
+        self.debug_id = None;
+    }
+
+    /// The `//# debugId=<id>` comment to append to the generated file, if `debugId` is set.
+    pub fn debug_id_comment(&self) -> Option<String> {
+        self.get_debug_id().map(|id| format!("{}{id}", debug_id::COMMENT_PREFIX))
+    }
+
+    /// Return `debugId`, first generating a random (version 4) UUID if it is unset.
+    ///
+    /// Use [`ensure_debug_id_with_seed`](Self::ensure_debug_id_with_seed) for an id that is
+    /// reproducible across builds. Requires the `random-debug-id` feature.
+    #[cfg(feature = "random-debug-id")]
+    pub fn ensure_debug_id(&mut self) -> &str {
+        self.debug_id.get_or_insert_with(|| Cow::Owned(debug_id::random_uuid()))
+    }
+
+    /// Return `debugId`, first deriving a name-based (version 5) UUID from `seed` if it is unset,
+    /// so the same seed, such as the generated code, always gives the same id.
+    pub fn ensure_debug_id_with_seed(&mut self, seed: &[u8]) -> &str {
+        self.debug_id.get_or_insert_with(|| Cow::Owned(debug_id::uuid_from_seed(seed)))
+    }
+
     pub fn get_debug_id(&self) -> Option<&str> {
         self.debug_id.as_deref()
     }
@@ -188,6 +444,35 @@ impl<'a> SourceMap<'a> {
     /// Adjust `sources`.
     pub fn set_sources<S: AsRef<str>, I: IntoIterator<Item = S>>(&mut self, sources: I) {
         self.sources = sources.into_iter().map(|s| Cow::Owned(s.as_ref().to_owned())).collect();
+        self.cache.reset_sources();
+    }
+
+    /// Rename every `sources` entry equal to `from` (compared as stored, before `sourceRoot` is
+    /// applied) to `to`, keeping its id. Returns whether any entry matched.
+    pub fn rename_source_by_path(&mut self, from: &str, to: &str) -> bool {
+        let mut found = false;
+        for source in self.sources.iter_mut().filter(|source| *source == from) {
+            *source = Cow::Owned(to.to_owned());
+            found = true;
+        }
+        if found {
+            self.cache.reset_sources();
+        }
+        found
+    }
+
+    /// How many tokens map into each source, indexed by source id. Tokens without a source, or
+    /// with an id past the end of `sources`, are not counted.
+    pub fn token_counts_by_source(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.sources.len()];
+        for token in self.tokens.iter() {
+            if let Some(count) =
+                token.get_source_id().and_then(|source_id| counts.get_mut(source_id as usize))
+            {
+                *count += 1;
+            }
+        }
+        counts
     }
 
     pub fn get_sources(&self) -> impl ExactSizeIterator<Item = &str> {
@@ -198,6 +483,36 @@ impl<'a> SourceMap<'a> {
     pub fn set_source_contents(&mut self, source_contents: Vec<Option<&str>>) {
         self.source_contents =
             source_contents.into_iter().map(|v| v.map(|s| Cow::Owned(s.to_owned()))).collect();
+        self.cache.reset_line_starts();
+    }
+
+    /// Drop all inlined source contents, e.g. for production maps whose sources are hosted
+    /// separately.
+    ///
+    /// Every entry becomes `None`, keeping `sources` and `source_contents` the same length. With no
+    /// content left, the encoders omit the optional `sourcesContent` field entirely rather than
+    /// emitting an array of `null`s.
+    pub fn clear_source_contents(&mut self) {
+        self.source_contents.iter_mut().for_each(|content| *content = None);
+        self.cache.reset_line_starts();
+    }
+
+    /// Fill in missing source contents, e.g. for maps shipped without `sourcesContent`.
+    ///
+    /// `f` is called with the path of every source whose content is `None` (or absent, when
+    /// `source_contents` is shorter than `sources`); a returned string is stored as its content.
+    /// Content that is already present is left untouched. The inverse of
+    /// [`clear_source_contents`](Self::clear_source_contents).
+    pub fn load_source_contents<F: FnMut(&str) -> Option<String>>(&mut self, mut f: F) {
+        if self.source_contents.len() < self.sources.len() {
+            self.source_contents.resize(self.sources.len(), None);
+        }
+        for (source, content) in self.sources.iter().zip(self.source_contents.iter_mut()) {
+            if content.is_none() {
+                *content = f(source).map(Cow::Owned);
+            }
+        }
+        self.cache.reset_line_starts();
     }
 
     pub fn get_source_contents(&self) -> impl ExactSizeIterator<Item = Option<&str>> {
@@ -208,6 +523,36 @@ impl<'a> SourceMap<'a> {
         self.tokens.get(index as usize).copied()
     }
 
+    /// Record where each token's generated and original ranges end, one entry per token in token
+    /// order. Encoding and decoding stay on standard v3 and ignore them.
+    ///
+    /// They are kept when tokens are rewritten in place (such as by [`Self::map_tokens`]) or
+    /// pushed with [`Self::try_push_token`], and dropped by anything that removes, reorders or
+    /// concatenates tokens.
+    ///
+    /// # Panics
+    ///
+    /// When `token_ends` does not have one entry per token.
+    pub fn set_token_ends(&mut self, token_ends: Vec<TokenEnd>) {
+        assert_eq!(token_ends.len(), self.tokens.len(), "one token end per token");
+        self.token_ends = Some(token_ends.into_boxed_slice());
+    }
+
+    /// Remove the end columns set with [`Self::set_token_ends`].
+    pub fn clear_token_ends(&mut self) {
+        self.token_ends = None;
+    }
+
+    /// End column of the generated range of the token at `index`, when recorded.
+    pub fn get_dst_end_col(&self, index: u32) -> Option<u32> {
+        self.token_ends.as_ref()?.get(index as usize)?.dst_end_col
+    }
+
+    /// End column of the original range of the token at `index`, when recorded.
+    pub fn get_src_end_col(&self, index: u32) -> Option<u32> {
+        self.token_ends.as_ref()?.get(index as usize)?.src_end_col
+    }
+
     pub fn get_source_view_token(&self, index: u32) -> Option<SourceViewToken<'_, 'a>> {
         self.tokens.get(index as usize).copied().map(|token| SourceViewToken::new(token, self))
     }
@@ -217,11 +562,110 @@ impl<'a> SourceMap<'a> {
         self.tokens.iter().copied()
     }
 
+    /// Like [`Self::get_tokens`], starting at token `start` instead of skipping up to it. Empty
+    /// when `start` is past the end.
+    pub fn get_tokens_from(&self, start: usize) -> impl ExactSizeIterator<Item = Token> {
+        self.tokens.get(start..).unwrap_or_default().iter().copied()
+    }
+
     /// Get source view tokens. See [`SourceViewToken`] for more information.
     pub fn get_source_view_tokens(&self) -> impl ExactSizeIterator<Item = SourceViewToken<'_, 'a>> {
         self.tokens.iter().map(|&token| SourceViewToken::new(token, self))
     }
 
+    /// Iterate the tokens mapping into source `source_id`, in generated order. A linear scan
+    /// over all tokens.
+    pub fn tokens_for_source(&self, source_id: u32) -> impl Iterator<Item = Token> + '_ {
+        self.tokens.iter().filter(move |token| token.get_source_id() == Some(source_id)).copied()
+    }
+
+    /// Whether the map has no tokens, i.e. an empty `mappings` string.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    #[inline]
+    pub fn token_count(&self) -> usize {
+        self.tokens.len()
+    }
+
+    #[inline]
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    #[inline]
+    pub fn name_count(&self) -> usize {
+        self.names.len()
+    }
+
+    /// The generated line of the last token, or `None` for an empty map.
+    /// Tokens are sorted by destination position, so this is the greatest `dst_line`.
+    #[inline]
+    pub fn max_dst_line(&self) -> Option<u32> {
+        self.tokens.last().map(|token| token.dst_line)
+    }
+
+    /// Estimate the heap bytes this map holds: the token array, `token_chunks`, the ignore list,
+    /// the string vectors and the bytes of every string.
+    ///
+    /// String bytes are counted whether they are owned or borrowed from an input buffer (for a
+    /// map from [`Self::from_json_string`], mostly the latter), so summing over maps that borrow
+    /// one buffer overcounts it.
+    pub fn memory_usage(&self) -> usize {
+        use std::mem::{size_of, size_of_val};
+        let strings = |items: &[Cow<'_, str>]| {
+            size_of_val(items) + items.iter().map(|s| s.len()).sum::<usize>()
+        };
+        let optional = |item: &Option<Cow<'_, str>>| item.as_ref().map_or(0, |s| s.len());
+        size_of::<Self>()
+            + size_of_val(&*self.tokens)
+            + self.token_chunks.as_ref().map_or(0, |c| c.capacity() * size_of::<TokenChunk>())
+            + self.x_google_ignore_list.as_ref().map_or(0, |l| l.capacity() * size_of::<u32>())
+            + strings(&self.names)
+            + strings(&self.sources)
+            + size_of_val(self.source_contents.as_slice())
+            + self.source_contents.iter().map(optional).sum::<usize>()
+            + optional(&self.file)
+            + optional(&self.source_root)
+            + optional(&self.debug_id)
+    }
+
+    /// A hash of the names, sources, source contents and tokens, for keying build caches.
+    ///
+    /// Stable across runs, platforms and crate versions, and independent of ownership, capacity
+    /// and `token_chunks`: equal maps hash equal once both are [`canonicalize`](Self::canonicalize)d.
+    /// This is FNV-1a and not a cryptographic hash.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a64::default();
+        hasher.write_u32(self.names.len() as u32);
+        for name in &self.names {
+            hasher.write_str(name);
+        }
+        hasher.write_u32(self.sources.len() as u32);
+        for (id, source) in self.sources.iter().enumerate() {
+            hasher.write_str(source);
+            match self.get_source_content(id as u32) {
+                Some(content) => {
+                    hasher.write(&[1]);
+                    hasher.write_str(content);
+                }
+                None => hasher.write(&[0]),
+            }
+        }
+        hasher.write_u32(self.tokens.len() as u32);
+        for token in self.tokens.iter() {
+            let (source_id, name_id) = token.raw_ids();
+            for field in
+                [token.dst_line, token.dst_col, token.src_line, token.src_col, source_id, name_id]
+            {
+                hasher.write_u32(field);
+            }
+        }
+        hasher.0
+    }
+
     pub fn get_name(&self, id: u32) -> Option<&str> {
         self.names.get(id as usize).map(AsRef::as_ref)
     }
@@ -230,71 +674,635 @@ impl<'a> SourceMap<'a> {
         self.sources.get(id as usize).map(AsRef::as_ref)
     }
 
+    /// The source path at `id` with `sourceRoot` prepended, as consumers resolve it. Borrowed when
+    /// there is nothing to prepend.
+    pub fn get_resolved_source(&self, id: u32) -> Option<Cow<'_, str>> {
+        let source = self.get_source(id)?;
+        Some(resolve_source(self.get_source_root(), source))
+    }
+
+    /// Every source with `sourceRoot` applied, in id order; the bulk form of
+    /// [`Self::get_resolved_source`].
+    pub fn sources_resolved(&self) -> impl ExactSizeIterator<Item = Cow<'_, str>> {
+        let source_root = self.get_source_root();
+        self.sources.iter().map(move |source| resolve_source(source_root, source))
+    }
+
+    /// Id of the first `names` entry equal to `name`.
+    ///
+    /// The first call builds a reverse index of all names, which later calls reuse until the
+    /// names change.
+    pub fn name_index(&self, name: &str) -> Option<u32> {
+        self.cache.names.get_or_init(|| first_occurrences(&self.names)).get(name).copied()
+    }
+
+    /// Id of the first `sources` entry equal to `path`, compared as stored (before `sourceRoot`
+    /// is applied). Indexed like [`Self::name_index`].
+    pub fn source_index(&self, path: &str) -> Option<u32> {
+        self.cache.sources.get_or_init(|| first_occurrences(&self.sources)).get(path).copied()
+    }
+
+    /// Add a name, deduplicating against the existing ones (see [`Self::name_index`]), and return
+    /// its id. A new name is copied and added to the index, so adding many names stays linear.
+    pub fn add_name(&mut self, name: &str) -> u32 {
+        if let Some(id) = self.name_index(name) {
+            return id;
+        }
+        let id = self.names.len() as u32;
+        let name: Cow<'a, str> = Cow::Owned(name.to_owned());
+        if let Some(ids) = self.cache.names.get_mut() {
+            ids.insert(name.clone(), id);
+        }
+        self.names.push(name);
+        id
+    }
+
+    /// Add a source and its content, deduplicating on the source path (the first content wins),
+    /// and return its id. New strings are copied; `source_contents` is padded to stay aligned
+    /// with `sources`. Like [`Self::add_name`], a new source is added to the index.
+    pub fn add_source_and_content(&mut self, source: &str, content: Option<&str>) -> u32 {
+        if let Some(id) = self.source_index(source) {
+            return id;
+        }
+        let id = self.sources.len() as u32;
+        let source: Cow<'a, str> = Cow::Owned(source.to_owned());
+        if let Some(ids) = self.cache.sources.get_mut() {
+            ids.insert(source.clone(), id);
+        }
+        self.source_contents.resize(self.sources.len(), None);
+        self.sources.push(source);
+        self.source_contents.push(content.map(|content| Cow::Owned(content.to_owned())));
+        if let Some(line_starts) = self.cache.line_starts.get_mut() {
+            line_starts.resize_with(self.source_contents.len(), OnceLock::new);
+        }
+        id
+    }
+
     pub fn get_source_content(&self, id: u32) -> Option<&str> {
         self.source_contents.get(id as usize).and_then(|item| item.as_deref())
     }
 
+    /// Byte offset of the start of every line in the content of source `id`, splitting on all
+    /// JavaScript line terminators (`\n`, `\r\n`, `\r`, U+2028 and U+2029). `src_line` indexes
+    /// into the result, so a line's text is `content[starts[line]..starts[line + 1]]`, including
+    /// its terminator. `None` when the source has no content.
+    ///
+    /// Computed on the first call for each source and reused until its content changes.
+    pub fn source_line_starts(&self, id: u32) -> Option<&[u32]> {
+        let content = self.get_source_content(id)?;
+        let line_starts_by_source = self.cache.line_starts.get_or_init(|| {
+            std::iter::repeat_with(OnceLock::new).take(self.source_contents.len()).collect()
+        });
+        Some(line_starts_by_source[id as usize].get_or_init(|| line_starts(content)))
+    }
+
+    /// Byte offset into the content of source `id` of the position `line`/`col`, with `col` in
+    /// UTF-16 code units as in `mappings`. `None` when the source has no content or the position
+    /// is past the end of its line.
+    ///
+    /// The line starts are cached per source, see [`Self::source_line_starts`], so only the line
+    /// itself is scanned.
+    pub fn source_byte_offset(&self, id: u32, line: u32, col: u32) -> Option<usize> {
+        let content = self.get_source_content(id)?;
+        let starts = self.source_line_starts(id)?;
+        let start = *starts.get(line as usize)? as usize;
+        let end = starts.get(line as usize + 1).map_or(content.len(), |&end| end as usize);
+        let text = content[start..end].trim_end_matches(LINE_TERMINATORS);
+        let mut utf16 = 0;
+        for (offset, ch) in text.char_indices() {
+            if utf16 >= col {
+                return (utf16 == col).then_some(start + offset);
+            }
+            utf16 += ch.len_utf16() as u32;
+        }
+        (utf16 == col).then_some(start + text.len())
+    }
+
     pub fn get_source_and_content(&self, id: u32) -> Option<(&str, &str)> {
         let source = self.get_source(id)?;
         let content = self.get_source_content(id)?;
         Some((source, content))
     }
 
-    /// Generate a lookup table, it will be used at `lookup_token` or `lookup_source_view_token`.
-    pub fn generate_lookup_table(&self) -> Vec<LineLookupTable<'_>> {
-        // The dst line/dst col always has increasing order.
-        if let Some(last_token) = self.tokens.last() {
-            let mut table = vec![&self.tokens[..0]; last_token.dst_line as usize + 1];
-            let mut prev_start_idx = 0u32;
-            let mut prev_dst_line = 0u32;
-            for (idx, token) in self.tokens.iter().enumerate() {
-                if token.dst_line != prev_dst_line {
-                    table[prev_dst_line as usize] = &self.tokens[prev_start_idx as usize..idx];
-                    prev_start_idx = idx as u32;
-                    prev_dst_line = token.dst_line;
+    /// Append `token` after checking that its source and name ids exist, so a bad reference fails
+    /// here instead of at encode or lookup time.
+    ///
+    /// Each push reallocates the token slice; build large maps with [`crate::SourceMapBuilder`].
+    ///
+    /// # Errors
+    ///
+    /// [`Error::BadSourceReference`] / [`Error::BadNameReference`] for an out-of-range id.
+    pub fn try_push_token(&mut self, token: Token) -> Result<()> {
+        if let Some(source_id) = token.get_source_id()
+            && source_id as usize >= self.sources.len()
+        {
+            return Err(Error::BadSourceReference(source_id));
+        }
+        if let Some(name_id) = token.get_name_id()
+            && name_id as usize >= self.names.len()
+        {
+            return Err(Error::BadNameReference(name_id));
+        }
+        let mut tokens = std::mem::take(&mut self.tokens).into_vec();
+        tokens.push(token);
+        self.tokens = tokens.into_boxed_slice();
+        if let Some(token_ends) = self.token_ends.take() {
+            let mut token_ends = token_ends.into_vec();
+            token_ends.push(TokenEnd::default());
+            self.token_ends = Some(token_ends.into_boxed_slice());
+        }
+        // The last chunk runs to the end of the tokens, so it takes the new one.
+        if let Some(chunks) = self.token_chunks.as_mut() {
+            match chunks.last_mut() {
+                Some(chunk) => chunk.end += 1,
+                None => chunks.push(TokenChunk::new(0, 1, 0, 0, 0, 0, 0, 0)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Shift every token's generated line by `delta`, e.g. after prepending a `delta`-line banner
+    /// to the generated code. Cheaper than rebuilding through [`crate::ConcatSourceMapBuilder`].
+    ///
+    /// `token_chunks` are kept in sync. With a negative `delta`, tokens that would land before
+    /// line 0 are dropped (their generated code was removed); with a positive one, lines past
+    /// `u32::MAX` saturate. In either case the chunk boundaries no longer apply and
+    /// `token_chunks` is cleared.
+    ///
+    /// Returns how many tokens were dropped, always 0 for a non-negative `delta`.
+    pub fn offset_dst_lines(&mut self, delta: i32) -> usize {
+        if delta == 0 {
+            return 0;
+        }
+        if self.tokens.iter().all(|token| token.dst_line.checked_add_signed(delta).is_some()) {
+            for token in &mut self.tokens {
+                token.dst_line = token.dst_line.wrapping_add_signed(delta);
+            }
+            if let Some(chunks) = self.token_chunks.as_mut() {
+                // A chunk at the start of the tokens encodes its leading `;`s relative to line 0,
+                // so only chunks continuing from a previous token carry a shifted line.
+                for chunk in chunks.iter_mut().filter(|chunk| chunk.start != 0) {
+                    chunk.prev_dst_line = chunk.prev_dst_line.saturating_add_signed(delta);
                 }
             }
-            table[prev_dst_line as usize] = &self.tokens[prev_start_idx as usize..];
-            table
+            0
+        } else if delta > 0 {
+            for token in &mut self.tokens {
+                token.dst_line = token.dst_line.saturating_add_signed(delta);
+            }
+            self.token_chunks = None;
+            0
         } else {
-            vec![]
+            let len = self.tokens.len();
+            self.tokens = self
+                .tokens
+                .iter()
+                .filter_map(|&(mut token)| {
+                    token.dst_line = token.dst_line.checked_add_signed(delta)?;
+                    Some(token)
+                })
+                .collect();
+            self.token_chunks = None;
+            if self.tokens.len() != len {
+                self.token_ends = None;
+            }
+            len - self.tokens.len()
         }
     }
 
-    /// Lookup a token by line and column, it will used at remapping.
-    #[inline]
-    pub fn lookup_token(
-        &self,
-        lookup_table: &[LineLookupTable],
-        line: u32,
-        col: u32,
-    ) -> Option<Token> {
-        // If the line is greater than the number of lines in the lookup table, it hasn't corresponding origin token.
-        if line >= lookup_table.len() as u32 {
-            return None;
+    /// Concatenate `(map, line_offset)` pairs into a new map, the one-call form of
+    /// [`ConcatSourceMapBuilder::from_sourcemaps`] followed by
+    /// [`into_sourcemap`](ConcatSourceMapBuilder::into_sourcemap).
+    pub fn concat(sourcemap_and_line_offsets: &[(&'a SourceMap<'_>, u32)]) -> Self {
+        ConcatSourceMapBuilder::from_sourcemaps(sourcemap_and_line_offsets).into_sourcemap()
+    }
+
+    /// Append `other` in place, its generated lines shifted by `line_offset`, as
+    /// [`crate::ConcatSourceMapBuilder::add_sourcemap`] would — including dropping `other`'s first
+    /// token when it duplicates the last one already here.
+    ///
+    /// `other`'s sources and names are appended after the existing ones (not deduplicated), its
+    /// `x_google_ignoreList` entries are carried over with corrected ids, and a [`TokenChunk`] is
+    /// recorded for its tokens so encoding stays parallel.
+    ///
+    /// Like the builder's output, the result has no `sourceRoot`: both maps' roots are folded into
+    /// their sources. A `debug_id` is kept when only one side has it or both agree, and cleared
+    /// when they differ; unlike the builder, a later `append` does not remember that conflict.
+    pub fn append(&mut self, other: &SourceMap<'a>, line_offset: u32) {
+        let source_offset = self.sources.len() as u32;
+        let name_offset = self.names.len() as u32;
+        self.cache = SourceMapCache::default();
+        self.token_ends = None;
+
+        if let Some(source_root) = self.source_root.take() {
+            for source in &mut self.sources {
+                if let Cow::Owned(resolved) = resolve_source(Some(&source_root), source) {
+                    *source = Cow::Owned(resolved);
+                }
+            }
         }
-        // Every token in `lookup_table[line]` has `dst_line == line` (see `generate_lookup_table`),
-        // so the line component of the key is constant across the slice. Binary searching on
-        // `dst_col` alone avoids building and lexicographically comparing a `(dst_line, dst_col)`
-        // tuple on every probe — measurably faster on this remapping hot path.
-        let token = greatest_lower_bound(lookup_table[line as usize], &col, |token| token.dst_col)?;
-        Some(*token)
+        // Keep `source_contents` aligned with `sources` on both sides of the join.
+        self.source_contents.resize(self.sources.len(), None);
+        let other_root = other.get_source_root();
+        self.sources.extend(other.sources.iter().map(|source| {
+            match resolve_source(other_root, source) {
+                Cow::Owned(resolved) => Cow::Owned(resolved),
+                Cow::Borrowed(_) => source.clone(),
+            }
+        }));
+        self.source_contents.extend(other.source_contents.iter().cloned());
+        self.source_contents.resize(self.sources.len(), None);
+        self.names.extend(other.names.iter().cloned());
+        if let Some(ignore_list) = &other.x_google_ignore_list {
+            self.x_google_ignore_list
+                .get_or_insert_default()
+                .extend(ignore_list.iter().map(|id| id + source_offset));
+        }
+        match (&self.debug_id, &other.debug_id) {
+            (None, Some(debug_id)) => self.debug_id = Some(debug_id.clone()),
+            (Some(existing), Some(debug_id)) if existing != debug_id => self.debug_id = None,
+            _ => {}
+        }
+
+        let mut tokens = std::mem::take(&mut self.tokens).into_vec();
+        let chunks = self.token_chunks.get_or_insert_with(|| {
+            if tokens.is_empty() {
+                vec![]
+            } else {
+                vec![TokenChunk::new(0, tokens.len() as u32, 0, 0, 0, 0, 0, 0)]
+            }
+        });
+        // The VLQ baseline for the new chunk is the last source/name id committed so far.
+        let mut prev_source_id =
+            tokens.iter().rev().find_map(Token::get_source_id).unwrap_or_default();
+        let mut prev_name_id = tokens.iter().rev().find_map(Token::get_name_id).unwrap_or_default();
+        let chunk = crate::concat_sourcemap_builder::append_tokens(
+            &mut tokens,
+            &other.tokens,
+            (line_offset, source_offset, name_offset),
+            &mut prev_source_id,
+            &mut prev_name_id,
+        );
+        chunks.push(chunk);
+        self.tokens = tokens.into_boxed_slice();
     }
 
-    /// Lookup a token by line and column, it will used at remapping. See `SourceViewToken`.
-    pub fn lookup_source_view_token(
-        &self,
-        lookup_table: &[LineLookupTable],
-        line: u32,
-        col: u32,
-    ) -> Option<SourceViewToken<'_, 'a>> {
-        self.lookup_token(lookup_table, line, col).map(|token| SourceViewToken::new(token, self))
+    /// Rewrite every token in place through `f`, e.g. to shift columns after tab expansion.
+    ///
+    /// `f` must keep tokens sorted by generated position, or lookups stop working. Lookup tables
+    /// from [`generate_lookup_table`](Self::generate_lookup_table) taken before the call are stale
+    /// and must be regenerated; `token_chunks` are kept in sync.
+    pub fn map_tokens<F: FnMut(Token) -> Token>(&mut self, mut f: F) {
+        for token in &mut self.tokens {
+            *token = f(*token);
+        }
+        self.refresh_token_chunks();
     }
 
-    /// Like [`lookup_token`](Self::lookup_token), but clamps to the line's first token when `col`
-    /// falls *before* it, instead of returning `None`.
+    /// Replace all tokens with those from `tokens`, e.g. a filtered or transformed
+    /// [`get_tokens`](Self::get_tokens). The tokens must be sorted by generated position for
+    /// lookups; `token_chunks` are cleared.
+    pub fn set_tokens_from_iter<I: IntoIterator<Item = Token>>(&mut self, tokens: I) {
+        self.tokens = tokens.into_iter().collect();
+        self.token_chunks = None;
+        self.token_ends = None;
+    }
+
+    /// Split the tokens into about `target_chunks` [`TokenChunk`]s at generated line boundaries,
+    /// replacing any existing `token_chunks`, e.g. for a map decoded from JSON (which has none).
     ///
-    /// `lookup_token` returns the greatest token with destination `<= (line, col)`, so a query
+    /// The encoded `mappings` are unchanged; each chunk just carries the state needed to encode
+    /// it independently. A single line is never split, so there may be fewer chunks.
+    pub fn rechunk(&mut self, target_chunks: usize) {
+        let len = self.tokens.len();
+        let target = target_chunks.clamp(1, len.max(1));
+        let mut chunks = Vec::with_capacity(target);
+        let mut start = 0;
+        for i in 1..target {
+            // Move each even split point forward to the start of the next line.
+            let mut split = (len * i / target).max(start + 1);
+            while split < len && self.tokens[split].dst_line == self.tokens[split - 1].dst_line {
+                split += 1;
+            }
+            if split >= len {
+                break;
+            }
+            chunks.push(TokenChunk {
+                start: start as u32,
+                end: split as u32,
+                ..TokenChunk::default()
+            });
+            start = split;
+        }
+        chunks.push(TokenChunk { start: start as u32, end: len as u32, ..TokenChunk::default() });
+        self.token_chunks = Some(chunks);
+        self.refresh_token_chunks();
+    }
+
+    /// Recompute each chunk's VLQ baseline from the token preceding it, after tokens changed.
+    fn refresh_token_chunks(&mut self) {
+        let Some(chunks) = self.token_chunks.as_mut() else { return };
+        let (mut prev_source_id, mut prev_name_id) = (0, 0);
+        let mut scanned = 0;
+        for chunk in chunks.iter_mut().filter(|chunk| chunk.start != 0) {
+            let start = chunk.start as usize;
+            for token in self.tokens.get(scanned..start).into_iter().flatten() {
+                prev_source_id = token.get_source_id().unwrap_or(prev_source_id);
+                prev_name_id = token.get_name_id().unwrap_or(prev_name_id);
+            }
+            scanned = scanned.max(start);
+            let prev = &self.tokens[start - 1];
+            chunk.prev_dst_line = prev.dst_line;
+            chunk.prev_dst_col = prev.dst_col;
+            chunk.prev_src_line = prev.src_line;
+            chunk.prev_src_col = prev.src_col;
+            chunk.prev_source_id = prev_source_id;
+            chunk.prev_name_id = prev_name_id;
+        }
+    }
+
+    /// Keep only the tokens for which `f` returns `true`, compacting them in place in one pass.
+    ///
+    /// Removing tokens shifts token indices, so `token_chunks` are cleared when any is removed.
+    pub fn retain_tokens<F: FnMut(&Token) -> bool>(&mut self, f: F) {
+        let mut tokens = std::mem::take(&mut self.tokens).into_vec();
+        let len = tokens.len();
+        tokens.retain(f);
+        if tokens.len() != len {
+            self.token_chunks = None;
+            self.token_ends = None;
+        }
+        self.tokens = tokens.into_boxed_slice();
+    }
+
+    /// Remove tokens identical in every field to the token before them, such as those left
+    /// behind by concatenation. Clears `token_chunks` like [`Self::retain_tokens`].
+    pub fn compact(&mut self) {
+        let mut prev = None;
+        self.retain_tokens(|token| prev.replace(*token) != Some(*token));
+    }
+
+    /// Keep only the tokens on generated lines within `line_range`, at their current positions
+    /// (nothing is rebased). Clears `token_chunks` like [`Self::retain_tokens`].
+    pub fn retain_dst_range(&mut self, line_range: Range<u32>) {
+        self.retain_tokens(|token| line_range.contains(&token.dst_line));
+    }
+
+    /// Remove every token mapping into an `x_google_ignoreList` source, e.g. to keep third-party
+    /// code out of a shipped map.
+    ///
+    /// The ignored `sources` entries themselves are kept, so ids stay valid. Token indices shift,
+    /// so `token_chunks` are cleared.
+    pub fn drop_ignored_tokens(&mut self) {
+        let is_ignored =
+            |token: &Token| token.get_source_id().is_some_and(|id| self.is_source_ignored(id));
+        if !self.tokens.iter().any(is_ignored) {
+            return;
+        }
+        let tokens = self.tokens.iter().filter(|token| !is_ignored(token)).copied().collect();
+        self.tokens = tokens;
+        self.token_chunks = None;
+        self.token_ends = None;
+    }
+
+    /// Bring the tokens into a normal form: sorted by generated position (see [`Token`]'s
+    /// ordering) with exact duplicates removed, so maps built different ways encode identically.
+    ///
+    /// A no-op for lookups on a map whose tokens were already sorted. `token_chunks` are cleared
+    /// when anything changes.
+    pub fn canonicalize(&mut self) {
+        if self.tokens.is_sorted_by(|a, b| a < b) {
+            return;
+        }
+        let mut tokens = std::mem::take(&mut self.tokens).into_vec();
+        tokens.sort_unstable();
+        tokens.dedup();
+        self.tokens = tokens.into_boxed_slice();
+        self.token_chunks = None;
+        self.token_ends = None;
+    }
+
+    /// Merge `sources` entries with identical paths into the first occurrence.
+    ///
+    /// Tokens, `token_chunks` and `x_google_ignoreList` are rewritten to the surviving ids. A
+    /// merged source keeps the first non-`None` content among its duplicates.
+    pub fn dedupe_sources(&mut self) {
+        let Some((remap, len)) = dedupe_remap(&self.sources) else { return };
+        if !self.source_contents.is_empty() {
+            let mut contents = vec![None; len];
+            for (old, content) in std::mem::take(&mut self.source_contents).into_iter().enumerate()
+            {
+                let slot = &mut contents[remap[old] as usize];
+                if slot.is_none() {
+                    *slot = content;
+                }
+            }
+            self.source_contents = contents;
+        }
+        retain_first_occurrences(&mut self.sources, &remap);
+        self.cache.reset_sources();
+        self.cache.reset_line_starts();
+        if let Some(ignore_list) = self.x_google_ignore_list.as_mut() {
+            let mut seen = vec![false; len];
+            ignore_list.retain_mut(|id| {
+                let Some(&new) = remap.get(*id as usize) else { return true };
+                *id = new;
+                !std::mem::replace(&mut seen[new as usize], true)
+            });
+        }
+        self.remap_token_ids(Some(&remap), None);
+    }
+
+    /// Merge `names` entries with identical values into the first occurrence, rewriting tokens
+    /// and `token_chunks` to the surviving ids.
+    pub fn dedupe_names(&mut self) {
+        let Some((remap, _)) = dedupe_remap(&self.names) else { return };
+        retain_first_occurrences(&mut self.names, &remap);
+        self.cache.reset_names();
+        self.remap_token_ids(None, Some(&remap));
+    }
+
+    /// Rewrite source/name ids of tokens and chunks through `old id -> new id` tables.
+    fn remap_token_ids(&mut self, sources: Option<&[u32]>, names: Option<&[u32]>) {
+        let apply = |remap: Option<&[u32]>, id: u32| {
+            remap.and_then(|remap| remap.get(id as usize)).copied().unwrap_or(id)
+        };
+        for token in &mut self.tokens {
+            let (source_id, name_id) = token.raw_ids();
+            token.set_raw_ids(apply(sources, source_id), apply(names, name_id));
+        }
+        for chunk in self.token_chunks.iter_mut().flatten() {
+            chunk.prev_source_id = apply(sources, chunk.prev_source_id);
+            chunk.prev_name_id = apply(names, chunk.prev_name_id);
+        }
+    }
+
+    /// Iterate the tokens grouped by generated line, as `(dst_line, tokens_on_that_line)` in line
+    /// order. Lines without mappings are skipped, so line numbers may have gaps.
+    pub fn iter_lines(&self) -> impl Iterator<Item = (u32, &[Token])> {
+        self.tokens.chunk_by(|a, b| a.dst_line == b.dst_line).map(|line| (line[0].dst_line, line))
+    }
+
+    /// Whether the tokens are sorted by generated position, which lookup tables rely on.
+    ///
+    /// Not guaranteed for decoded maps, since the decoder accepts negative column deltas within a
+    /// line, nor for a map built from tokens added out of order (see
+    /// [`canonicalize`](Self::canonicalize)).
+    pub fn is_sorted(&self) -> bool {
+        self.tokens.is_sorted_by_key(|token| (token.dst_line, token.dst_col))
+    }
+
+    /// Like [`Self::is_sorted`], but reporting where the order breaks: out-of-order tokens
+    /// encode as negative column or line deltas, which some consumers reject.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::UnsortedToken`] with the index of the first token positioned before its
+    /// predecessor.
+    pub fn assert_monotonic_dst(&self) -> Result<()> {
+        let position = |token: &Token| (token.dst_line, token.dst_col);
+        match self.tokens.windows(2).position(|pair| position(&pair[1]) < position(&pair[0])) {
+            Some(index) => Err(Error::UnsortedToken(index as u32 + 1)),
+            None => Ok(()),
+        }
+    }
+
+    /// Generate a lookup table, it will be used at `lookup_token` or `lookup_source_view_token`.
+    ///
+    /// Lookups are only exact when the tokens are [sorted](Self::is_sorted); use
+    /// [`Self::assert_monotonic_dst`] to reject maps that are not.
+    pub fn generate_lookup_table(&self) -> Vec<LineLookupTable<'_>> {
+        // The dst line/dst col always has increasing order.
+        if let Some(last_token) = self.tokens.last() {
+            let mut table = vec![&self.tokens[..0]; last_token.dst_line as usize + 1];
+            let mut prev_start_idx = 0u32;
+            let mut prev_dst_line = 0u32;
+            for (idx, token) in self.tokens.iter().enumerate() {
+                if token.dst_line != prev_dst_line {
+                    table[prev_dst_line as usize] = &self.tokens[prev_start_idx as usize..idx];
+                    prev_start_idx = idx as u32;
+                    prev_dst_line = token.dst_line;
+                }
+            }
+            table[prev_dst_line as usize] = &self.tokens[prev_start_idx as usize..];
+            table
+        } else {
+            vec![]
+        }
+    }
+
+    /// Generate a [`SparseLookupTable`], which stores only the lines that have tokens.
+    ///
+    /// [`generate_lookup_table`](Self::generate_lookup_table) allocates an entry for every line up
+    /// to the last mapped one. Prefer this table for maps whose few mappings sit on high lines,
+    /// at the cost of a binary search over lines on each lookup.
+    pub fn generate_sparse_lookup_table(&self) -> SparseLookupTable<'_> {
+        let mut lines = vec![];
+        let mut start = 0;
+        for (line, tokens) in self.iter_lines() {
+            lines.push((line, start));
+            start += tokens.len() as u32;
+        }
+        SparseLookupTable { tokens: &self.tokens, lines }
+    }
+
+    /// Whether tokens with a source are in original-position order, `(source_id, src_line,
+    /// src_col)`, so [`build_reverse_lookup`](Self::build_reverse_lookup) needs no sort. Tokens
+    /// without a source are skipped. Typical for a map straight from a single-file transform.
+    pub fn is_sorted_by_source(&self) -> bool {
+        self.tokens
+            .iter()
+            .filter_map(|token| Some((token.get_source_id()?, token.src_line, token.src_col)))
+            .is_sorted()
+    }
+
+    /// Build a [`ReverseLookupTable`] for finding the generated position of an original one.
+    ///
+    /// Like [`generate_lookup_table`](Self::generate_lookup_table), the table is not stored on the
+    /// map; build it once and keep it for many queries.
+    pub fn build_reverse_lookup(&self) -> ReverseLookupTable<'_> {
+        let mut indices: Vec<u32> = (0..self.tokens.len() as u32)
+            .filter(|&index| self.tokens[index as usize].get_source_id().is_some())
+            .collect();
+        if !self.is_sorted_by_source() {
+            // Stable, so tokens sharing an original position stay in generated order.
+            indices.sort_by_key(|&index| {
+                let token = &self.tokens[index as usize];
+                (token.raw_ids().0, token.src_line, token.src_col)
+            });
+        }
+        ReverseLookupTable { tokens: &self.tokens, indices }
+    }
+
+    /// Lookup a token by line and column, it will used at remapping.
+    #[inline]
+    pub fn lookup_token(
+        &self,
+        lookup_table: &[LineLookupTable],
+        line: u32,
+        col: u32,
+    ) -> Option<Token> {
+        // If the line is greater than the number of lines in the lookup table, it hasn't corresponding origin token.
+        if line >= lookup_table.len() as u32 {
+            return None;
+        }
+        // Every token in `lookup_table[line]` has `dst_line == line` (see `generate_lookup_table`),
+        // so the line component of the key is constant across the slice. Binary searching on
+        // `dst_col` alone avoids building and lexicographically comparing a `(dst_line, dst_col)`
+        // tuple on every probe — measurably faster on this remapping hot path.
+        let token = greatest_lower_bound(lookup_table[line as usize], &col, |token| token.dst_col)?;
+        Some(*token)
+    }
+
+    /// Like [`lookup_token`](Self::lookup_token), but returns the token's index so neighbouring
+    /// tokens can be inspected with [`get_token`](Self::get_token).
+    ///
+    /// `lookup_table` must come from [`generate_lookup_table`](Self::generate_lookup_table) on
+    /// this map.
+    pub fn lookup_token_index(
+        &self,
+        lookup_table: &[LineLookupTable],
+        line: u32,
+        col: u32,
+    ) -> Option<usize> {
+        let line_tokens = *lookup_table.get(line as usize)?;
+        let index = greatest_lower_bound_index(line_tokens, &col, |token| token.dst_col)?;
+        // The table's line holds the same tokens as this map's run of tokens on `line`.
+        let line_start = self.tokens.partition_point(|token| token.dst_line < line);
+        Some(line_start + index)
+    }
+
+    /// Binary search the [sorted](Self::is_sorted) tokens for generated position `(line, col)`,
+    /// like [`slice::binary_search`]: `Ok` with the index of a token at that position (any one,
+    /// if several share it), or `Err` with the index where a token there would be inserted.
+    pub fn binary_search_dst(&self, line: u32, col: u32) -> std::result::Result<usize, usize> {
+        self.tokens.binary_search_by_key(&(line, col), |token| (token.dst_line, token.dst_col))
+    }
+
+    /// All tokens on generated `line`, in column order; empty for a line without mappings or past
+    /// the end of `lookup_table`.
+    pub fn tokens_on_line<'t>(
+        &self,
+        lookup_table: &[LineLookupTable<'t>],
+        line: u32,
+    ) -> &'t [Token] {
+        lookup_table.get(line as usize).copied().unwrap_or_default()
+    }
+
+    /// Lookup a token by line and column, it will used at remapping. See `SourceViewToken`.
+    pub fn lookup_source_view_token(
+        &self,
+        lookup_table: &[LineLookupTable],
+        line: u32,
+        col: u32,
+    ) -> Option<SourceViewToken<'_, 'a>> {
+        self.lookup_token(lookup_table, line, col).map(|token| SourceViewToken::new(token, self))
+    }
+
+    /// Like [`lookup_token`](Self::lookup_token), but clamps to the line's first token when `col`
+    /// falls *before* it, instead of returning `None`.
+    ///
+    /// `lookup_token` returns the greatest token with destination `<= (line, col)`, so a query
     /// before a line's first token yields `None`. That is correct for a plain position lookup, but
     /// it drops mappings when *composing* sourcemaps — e.g. remapping a token whose column lands
     /// before the previous map's first token on that line (a line indented past column 0). Clamping
@@ -331,6 +1339,55 @@ impl<'a> SourceMap<'a> {
     }
 }
 
+/// Compares every field by value except `token_chunks`, which only guides encoding, and the lazily
+/// built indexes, which are derived from the other fields. Equal maps therefore give the same
+/// lookups and encode to equivalent JSON. A source without content equals one with `None`
+/// content, as they encode the same.
+impl<'b> PartialEq<SourceMap<'b>> for SourceMap<'_> {
+    fn eq(&self, other: &SourceMap<'b>) -> bool {
+        self.file.as_deref() == other.file.as_deref()
+            && self.source_root.as_deref() == other.source_root.as_deref()
+            && self.names == other.names
+            && self.sources == other.sources
+            && (0..self.sources.len() as u32)
+                .all(|id| self.get_source_content(id) == other.get_source_content(id))
+            && self.tokens == other.tokens
+            && self.token_ends == other.token_ends
+            && self.x_google_ignore_list == other.x_google_ignore_list
+            && self.debug_id.as_deref() == other.debug_id.as_deref()
+    }
+}
+
+impl Eq for SourceMap<'_> {}
+
+/// Parses with [`SourceMap::from_json_string`], copying the strings since the result cannot
+/// borrow from the input.
+impl FromStr for SourceMap<'static> {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        SourceMap::from_json_string(value).map(SourceMap::into_owned)
+    }
+}
+
+/// Same as [`SourceMap::from_json_slice`].
+impl<'a> TryFrom<&'a [u8]> for SourceMap<'a> {
+    type Error = Error;
+
+    fn try_from(value: &'a [u8]) -> Result<Self> {
+        SourceMap::from_json_slice(value)
+    }
+}
+
+/// Same as [`SourceMap::from_json`].
+impl TryFrom<JSONSourceMap> for SourceMap<'static> {
+    type Error = Error;
+
+    fn try_from(value: JSONSourceMap) -> Result<Self> {
+        SourceMap::from_json(value)
+    }
+}
+
 /// Owned destructured parts of a [`SourceMap`].
 ///
 /// Returned by [`SourceMap::into_parts`] for downstream code that wants to
@@ -356,19 +1413,246 @@ impl<'a> From<SourceMapParts<'a>> for SourceMap<'a> {
     }
 }
 
+/// Field-by-field constructor returned by [`SourceMap::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct SourceMapFieldBuilder<'a> {
+    parts: SourceMapParts<'a>,
+    token_ends: Option<Box<[TokenEnd]>>,
+}
+
+impl<'a> SourceMapFieldBuilder<'a> {
+    pub fn file<S: Into<Cow<'a, str>>>(mut self, file: S) -> Self {
+        self.parts.file = Some(file.into());
+        self
+    }
+
+    pub fn names<S: Into<Cow<'a, str>>, I: IntoIterator<Item = S>>(mut self, names: I) -> Self {
+        self.parts.names = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn source_root<S: Into<Cow<'a, str>>>(mut self, source_root: S) -> Self {
+        self.parts.source_root = Some(source_root.into());
+        self
+    }
+
+    pub fn sources<S: Into<Cow<'a, str>>, I: IntoIterator<Item = S>>(mut self, sources: I) -> Self {
+        self.parts.sources = sources.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn source_contents<S: Into<Cow<'a, str>>, I: IntoIterator<Item = Option<S>>>(
+        mut self,
+        source_contents: I,
+    ) -> Self {
+        self.parts.source_contents =
+            source_contents.into_iter().map(|content| content.map(Into::into)).collect();
+        self
+    }
+
+    pub fn tokens<T: Into<Box<[Token]>>>(mut self, tokens: T) -> Self {
+        self.parts.tokens = tokens.into();
+        self
+    }
+
+    pub fn token_chunks(mut self, token_chunks: Vec<TokenChunk>) -> Self {
+        self.parts.token_chunks = Some(token_chunks);
+        self
+    }
+
+    pub fn x_google_ignore_list(mut self, x_google_ignore_list: Vec<u32>) -> Self {
+        self.parts.x_google_ignore_list = Some(x_google_ignore_list);
+        self
+    }
+
+    pub fn debug_id<S: Into<Cow<'a, str>>>(mut self, debug_id: S) -> Self {
+        self.parts.debug_id = Some(debug_id.into());
+        self
+    }
+
+    /// End columns for the tokens, one per token; see [`SourceMap::set_token_ends`].
+    pub fn token_ends<T: Into<Box<[TokenEnd]>>>(mut self, token_ends: T) -> Self {
+        self.token_ends = Some(token_ends.into());
+        self
+    }
+
+    /// Finish the [`SourceMap::builder`] chain.
+    pub fn build(self) -> SourceMap<'a> {
+        let mut sourcemap = SourceMap::from_parts(self.parts);
+        sourcemap.token_ends = self.token_ends;
+        sourcemap
+    }
+}
+
+/// The JavaScript line terminators, which [`line_starts`] splits on (`\r\n` counts as one).
+pub(crate) const LINE_TERMINATORS: [char; 4] = ['\r', '\n', '\u{2028}', '\u{2029}'];
+
+/// Byte offsets where each line of `content` starts, see [`SourceMap::source_line_starts`].
+pub(crate) fn line_starts(content: &str) -> Vec<u32> {
+    let bytes = content.as_bytes();
+    let mut starts = vec![0];
+    for (i, ch) in content.char_indices() {
+        match ch {
+            // `\n` is a single ASCII byte, so peeking the next byte is correct even after
+            // multi-byte UTF-8.
+            '\r' if bytes.get(i + 1) == Some(&b'\n') => {}
+            '\r' | '\n' | '\u{2028}' | '\u{2029}' => starts.push((i + ch.len_utf8()) as u32),
+            _ => {}
+        }
+    }
+    starts
+}
+
+/// Prepend `source_root` to `source`, joining with a `/` when the root lacks one. Absolute paths
+/// and URLs are left as is.
+pub(crate) fn resolve_source<'s>(source_root: Option<&str>, source: &'s str) -> Cow<'s, str> {
+    match source_root {
+        Some(root) if !root.is_empty() && !source.starts_with('/') && !source.contains("://") => {
+            let separator = if root.ends_with('/') { "" } else { "/" };
+            Cow::Owned(format!("{root}{separator}{source}"))
+        }
+        _ => Cow::Borrowed(source),
+    }
+}
+
+/// Map each index of `items` to the index of its first equal item once duplicates are removed,
+/// along with the deduplicated length. `None` when there are no duplicates.
+fn dedupe_remap(items: &[Cow<'_, str>]) -> Option<(Vec<u32>, usize)> {
+    let mut ids = FxHashMap::with_capacity_and_hasher(items.len(), FxBuildHasher);
+    let remap: Vec<u32> = items
+        .iter()
+        .map(|item| {
+            let next = ids.len() as u32;
+            *ids.entry(&**item).or_insert(next)
+        })
+        .collect();
+    (ids.len() != items.len()).then_some((remap, ids.len()))
+}
+
+/// Drop the items that `remap` merges into an earlier one.
+fn retain_first_occurrences<T>(items: &mut Vec<T>, remap: &[u32]) {
+    let mut kept = 0u32;
+    let mut index = 0;
+    items.retain(|_| {
+        let first = remap[index] == kept;
+        kept += u32::from(first);
+        index += 1;
+        first
+    });
+}
+
+/// 64-bit FNV-1a, fed explicitly length-prefixed fields so the hash doesn't depend on
+/// `std::hash` implementation details.
+struct Fnv1a64(u64);
+
+impl Default for Fnv1a64 {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a64 {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_str(&mut self, value: &str) {
+        self.write_u32(value.len() as u32);
+        self.write(value.as_bytes());
+    }
+}
+
 type LineLookupTable<'a> = &'a [Token];
 
+/// Tokens ordered by original position, see [`SourceMap::build_reverse_lookup`].
+#[derive(Debug, Clone)]
+pub struct ReverseLookupTable<'a> {
+    tokens: &'a [Token],
+    /// Indices into `tokens` of the tokens with a source, sorted by original position.
+    indices: Vec<u32>,
+}
+
+impl ReverseLookupTable<'_> {
+    /// The first generated token for the greatest original position `<= (line, col)` on `line`
+    /// of `source_id`. `None` when the line has no mapping at or before `col`.
+    pub fn lookup_generated_position(&self, source_id: u32, line: u32, col: u32) -> Option<Token> {
+        let key = |index: &u32| {
+            let token = &self.tokens[*index as usize];
+            (token.raw_ids().0, token.src_line, token.src_col)
+        };
+        let end = self.indices.partition_point(|index| key(index) <= (source_id, line, col));
+        let last = *self.indices[..end].last()?;
+        let (found_source, found_line, found_col) = key(&last);
+        if (found_source, found_line) != (source_id, line) {
+            return None;
+        }
+        let start = self.indices[..end]
+            .partition_point(|index| key(index) < (found_source, found_line, found_col));
+        Some(self.tokens[self.indices[start] as usize])
+    }
+}
+
+/// Line lookup table holding only the generated lines that have tokens, see
+/// [`SourceMap::generate_sparse_lookup_table`].
+#[derive(Debug, Clone)]
+pub struct SparseLookupTable<'a> {
+    tokens: &'a [Token],
+    /// `(dst_line, index of the line's first token)`, sorted by line.
+    lines: Vec<(u32, u32)>,
+}
+
+impl<'a> SparseLookupTable<'a> {
+    /// Number of lines with tokens.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// All tokens on generated `line`, in column order; empty for a line without mappings.
+    pub fn tokens_on_line(&self, line: u32) -> &'a [Token] {
+        let Ok(index) = self.lines.binary_search_by_key(&line, |&(line, _)| line) else {
+            return &[];
+        };
+        let start = self.lines[index].1 as usize;
+        let end = self.lines.get(index + 1).map_or(self.tokens.len(), |&(_, end)| end as usize);
+        &self.tokens[start..end]
+    }
+
+    /// Same as [`SourceMap::lookup_token`] with a dense table.
+    pub fn lookup_token(&self, line: u32, col: u32) -> Option<Token> {
+        greatest_lower_bound(self.tokens_on_line(line), &col, |token| token.dst_col).copied()
+    }
+}
+
 fn greatest_lower_bound<'a, T, K: Ord, F: Fn(&'a T) -> K>(
     slice: &'a [T],
     key: &K,
     map: F,
 ) -> Option<&'a T> {
+    greatest_lower_bound_index(slice, key, map).map(|idx| &slice[idx])
+}
+
+pub(crate) fn greatest_lower_bound_index<'a, T, K: Ord, F: Fn(&'a T) -> K>(
+    slice: &'a [T],
+    key: &K,
+    map: F,
+) -> Option<usize> {
     let mut idx = match slice.binary_search_by_key(key, &map) {
         Ok(index) => index,
         Err(index) => {
             // If there is no match, then we know for certain that the index is where we should
             // insert a new token, and that the token directly before is the greatest lower bound.
-            return slice.get(index.checked_sub(1)?);
+            return index.checked_sub(1);
         }
     };
 
@@ -382,7 +1666,7 @@ fn greatest_lower_bound<'a, T, K: Ord, F: Fn(&'a T) -> K>(
             break;
         }
     }
-    slice.get(idx)
+    Some(idx)
 }
 
 #[cfg(test)]
@@ -565,4 +1849,1062 @@ mod tests {
         // The earliest token at column 7 wins (src_line 1).
         assert_eq!(sm.lookup_token(&table, 0, 7).unwrap().get_src_line(), 1);
     }
+
+    #[test]
+    fn stats() {
+        let empty = SourceMap::default();
+        assert!(empty.is_empty());
+        assert_eq!(empty.token_count(), 0);
+        assert_eq!(empty.source_count(), 0);
+        assert_eq!(empty.name_count(), 0);
+        assert_eq!(empty.max_dst_line(), None);
+
+        let sm = SourceMap::from_json_string(
+            r#"{"version":3,"sources":["a.js","b.js"],"names":["n"],"mappings":"AAAAA,EAAC;;ACAA"}"#,
+        )
+        .unwrap();
+        assert!(!sm.is_empty());
+        assert_eq!(sm.token_count(), 3);
+        assert_eq!(sm.source_count(), 2);
+        assert_eq!(sm.name_count(), 1);
+        assert_eq!(sm.max_dst_line(), Some(2));
+    }
+
+    #[test]
+    fn offset_dst_lines() {
+        let json = r#"{"version":3,"sources":["a.js"],"names":[],"mappings":"AAAA;AACA"}"#;
+        let mut sm = SourceMap::from_json_string(json).unwrap();
+        assert_eq!(sm.offset_dst_lines(2), 0);
+        let table = sm.generate_lookup_table();
+        assert!(sm.lookup_token(&table, 0, 0).is_none());
+        assert_eq!(sm.lookup_token(&table, 2, 0).unwrap().get_src_line(), 0);
+        assert_eq!(sm.lookup_token(&table, 3, 0).unwrap().get_src_line(), 1);
+        assert_eq!(
+            sm.to_json_string(),
+            SourceMap::from_json_string(
+                r#"{"version":3,"sources":["a.js"],"names":[],"mappings":";;AAAA;AACA"}"#
+            )
+            .unwrap()
+            .to_json_string()
+        );
+
+        // Shifting back up drops the tokens that would fall before line 0.
+        assert_eq!(sm.offset_dst_lines(-3), 1);
+        assert_eq!(
+            sm.get_tokens().collect::<Vec<_>>(),
+            vec![Token::new(0, 0, 1, 0, Some(0), None)]
+        );
+
+        // Shifting down past `u32::MAX` saturates rather than dropping tokens.
+        let mut sm = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![Cow::Borrowed("a.js")],
+            vec![],
+            vec![
+                Token::new(0, 0, 0, 0, Some(0), None),
+                Token::new(u32::MAX - 1, 0, 1, 0, Some(0), None),
+            ]
+            .into_boxed_slice(),
+            None,
+        );
+        assert_eq!(sm.offset_dst_lines(5), 0);
+        assert_eq!(
+            sm.get_tokens().collect::<Vec<_>>(),
+            vec![
+                Token::new(5, 0, 0, 0, Some(0), None),
+                Token::new(u32::MAX, 0, 1, 0, Some(0), None)
+            ]
+        );
+    }
+
+    #[test]
+    fn offset_dst_lines_updates_chunks() {
+        let tokens =
+            vec![Token::new(0, 0, 0, 0, Some(0), None), Token::new(1, 0, 1, 0, Some(0), None)];
+        let chunks =
+            vec![TokenChunk::new(0, 1, 0, 0, 0, 0, 0, 0), TokenChunk::new(1, 2, 0, 0, 0, 0, 0, 0)];
+        let mut sm = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![Cow::Borrowed("a.js")],
+            vec![],
+            tokens.into_boxed_slice(),
+            Some(chunks),
+        );
+        let expected = sm.to_json_string().replace(r#""mappings":""#, r#""mappings":";;;"#);
+        sm.offset_dst_lines(3);
+        let chunks = sm.token_chunks.as_ref().unwrap();
+        assert_eq!((chunks[0].prev_dst_line, chunks[1].prev_dst_line), (0, 3));
+        assert_eq!(sm.to_json_string(), expected);
+    }
+
+    #[test]
+    fn clear_source_contents() {
+        let json = r#"{"version":3,"sources":["a.js","b.js"],"sourcesContent":["secret a",null],"names":[],"mappings":"AAAA"}"#;
+        let mut sm = SourceMap::from_json_string(json).unwrap();
+        sm.clear_source_contents();
+        assert_eq!(sm.get_source_contents().collect::<Vec<_>>(), vec![None, None]);
+
+        let output = sm.to_json_string();
+        assert_eq!(
+            output,
+            r#"{"version":3,"names":[],"sources":["a.js","b.js"],"mappings":"AAAA"}"#
+        );
+        assert!(sm.to_json().sources_content.is_none());
+    }
+
+    #[test]
+    fn load_source_contents() {
+        let json = r#"{"version":3,"sources":["a.js","b.js","c.js"],"sourcesContent":["kept"],"names":[],"mappings":"AAAA"}"#;
+        let mut sm = SourceMap::from_json_string(json).unwrap();
+        let mut requested = vec![];
+        sm.load_source_contents(|source| {
+            requested.push(source.to_string());
+            (source == "b.js").then(|| "loaded b".to_string())
+        });
+        assert_eq!(requested, vec!["b.js", "c.js"]);
+        assert_eq!(
+            sm.get_source_contents().collect::<Vec<_>>(),
+            vec![Some("kept"), Some("loaded b"), None]
+        );
+    }
+
+    #[cfg(feature = "random-debug-id")]
+    #[test]
+    fn ensure_debug_id() {
+        let json = r#"{"version":3,"sources":["a.js"],"names":[],"mappings":"AAAA"}"#;
+        let mut sm = SourceMap::from_json_string(json).unwrap();
+        let id = sm.ensure_debug_id().to_string();
+        assert!(debug_id::is_valid_uuid(&id));
+        assert_ne!(SourceMap::from_json_string(json).unwrap().ensure_debug_id(), id);
+        // An existing id is kept.
+        assert_eq!(sm.ensure_debug_id(), id);
+        assert_eq!(sm.ensure_debug_id_with_seed(b"other"), id);
+    }
+
+    #[test]
+    fn ensure_debug_id_with_seed() {
+        let mut sm = SourceMap::default();
+        sm.set_debug_id("56431d54-c0a6-451d-8ea2-ba5de5d8ca2e");
+        assert_eq!(sm.ensure_debug_id_with_seed(b"other"), "56431d54-c0a6-451d-8ea2-ba5de5d8ca2e");
+
+        let mut seeded = SourceMap::default();
+        let seeded_id = seeded.ensure_debug_id_with_seed(b"let a = 1;").to_string();
+        assert_eq!(seeded_id, debug_id::uuid_from_seed(b"let a = 1;"));
+        assert_eq!(seeded.get_debug_id(), Some(seeded_id.as_str()));
+    }
+
+    #[test]
+    fn try_set_debug_id() {
+        let mut sm = SourceMap::default();
+        sm.try_set_debug_id("56431d54-c0a6-451d-8ea2-ba5de5d8ca2e").unwrap();
+        let err = sm.try_set_debug_id("not-a-uuid").unwrap_err();
+        assert!(matches!(err, Error::BadDebugId(ref id) if id == "not-a-uuid"));
+        assert_eq!(sm.get_debug_id(), Some("56431d54-c0a6-451d-8ea2-ba5de5d8ca2e"));
+    }
+
+    #[test]
+    fn debug_id_comment() {
+        let mut sm = SourceMap::default();
+        assert_eq!(sm.debug_id_comment(), None);
+        sm.set_debug_id("56431d54-c0a6-451d-8ea2-ba5de5d8ca2e");
+        assert_eq!(
+            sm.debug_id_comment().as_deref(),
+            Some("//# debugId=56431d54-c0a6-451d-8ea2-ba5de5d8ca2e")
+        );
+    }
+
+    #[test]
+    fn dedupe_sources() {
+        let json = r#"{
+            "version": 3,
+            "names": ["x", "y", "x"],
+            "sources": ["a.js", "b.js", "a.js"],
+            "sourcesContent": [null, "b", "a"],
+            "x_google_ignoreList": [0, 2],
+            "mappings": "AAAAA,CCAAC,CCAAC"
+        }"#;
+        let mut sm = SourceMap::from_json_string(json).unwrap();
+        sm.dedupe_sources();
+        assert_eq!(sm.get_sources().collect::<Vec<_>>(), vec!["a.js", "b.js"]);
+        assert_eq!(sm.get_source_contents().collect::<Vec<_>>(), vec![Some("a"), Some("b")]);
+        assert_eq!(sm.get_x_google_ignore_list(), Some(&[0][..]));
+        let source_ids: Vec<_> = sm.get_tokens().map(|t| t.get_source_id()).collect();
+        assert_eq!(source_ids, vec![Some(0), Some(1), Some(0)]);
+
+        sm.dedupe_names();
+        assert_eq!(sm.get_names().collect::<Vec<_>>(), vec!["x", "y"]);
+        let name_ids: Vec<_> = sm.get_tokens().map(|t| t.get_name_id()).collect();
+        assert_eq!(name_ids, vec![Some(0), Some(1), Some(0)]);
+        assert_eq!(
+            sm.to_json_string(),
+            r#"{"version":3,"names":["x","y"],"sources":["a.js","b.js"],"sourcesContent":["a","b"],"x_google_ignoreList":[0],"mappings":"AAAAA,CCAAC,CDAAD"}"#
+        );
+    }
+
+    #[test]
+    fn dedupe_sources_remaps_chunks() {
+        let tokens =
+            vec![Token::new(0, 0, 0, 0, Some(1), None), Token::new(0, 1, 0, 0, Some(2), None)];
+        let chunks =
+            vec![TokenChunk::new(0, 1, 0, 0, 0, 0, 0, 0), TokenChunk::new(1, 2, 0, 0, 0, 0, 0, 1)];
+        let mut sm = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![Cow::Borrowed("a.js"), Cow::Borrowed("b.js"), Cow::Borrowed("a.js")],
+            vec![],
+            tokens.into_boxed_slice(),
+            Some(chunks),
+        );
+        sm.dedupe_sources();
+        assert_eq!(sm.token_chunks.as_ref().unwrap()[1].prev_source_id, 1);
+        let mut unchunked = sm.clone();
+        unchunked.token_chunks = None;
+        assert_eq!(sm.to_json_string(), unchunked.to_json_string());
+    }
+
+    #[test]
+    fn to_json_string_pretty() {
+        let json = r#"{"version":3,"file":"out.js","sources":["a.js"],"sourcesContent":["a"],"names":["x"],"mappings":"AAAAA;AACA"}"#;
+        let sm = SourceMap::from_json_string(json).unwrap();
+        let pretty = sm.to_json_string_pretty();
+        assert!(pretty.contains("\n  \"mappings\": \"AAAAA;AACA\",\n"), "{pretty}");
+        // Unset optional fields are omitted, as in the compact output.
+        assert!(!pretty.contains("sourceRoot"), "{pretty}");
+        let reparsed = SourceMap::from_json_string(&pretty).unwrap();
+        assert_eq!(reparsed.to_json_string(), sm.to_json_string());
+    }
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SourceMap<'_>>();
+        assert_send_sync::<crate::OwnedSourceMap>();
+        assert_send_sync::<SourceViewToken<'_, '_>>();
+    }
+
+    #[test]
+    fn lookup_token_index() {
+        let json =
+            r#"{"version":3,"sources":["a.js"],"names":[],"mappings":"AAAA,EAAC,EAAC;;AACA,EAAC"}"#;
+        let sm = SourceMap::from_json_string(json).unwrap();
+        let table = sm.generate_lookup_table();
+        for (line, col, expected) in
+            [(0, 0, Some(0)), (0, 3, Some(1)), (0, 9, Some(2)), (2, 2, Some(4))]
+        {
+            let index = sm.lookup_token_index(&table, line, col);
+            assert_eq!(index, expected);
+            let index = index.unwrap() as u32;
+            assert_eq!(sm.get_token(index), sm.lookup_token(&table, line, col));
+        }
+        assert_eq!(sm.lookup_token_index(&table, 1, 0), None);
+        assert_eq!(sm.lookup_token_index(&table, 3, 0), None);
+    }
+
+    #[test]
+    fn append_matches_concat_builder() {
+        let a = SourceMap::new(
+            None,
+            vec!["foo".into()],
+            None,
+            vec!["a.js".into()],
+            vec![Some("a".into())],
+            vec![Token::new(0, 0, 0, 0, Some(0), Some(0)), Token::new(1, 0, 0, 4, None, None)]
+                .into_boxed_slice(),
+            None,
+        );
+        // No sources or names, and a first token equal to `a`'s last: dropped at the boundary.
+        let b = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![],
+            vec![],
+            vec![Token::new(1, 0, 0, 4, None, None), Token::new(2, 0, 0, 0, None, None)]
+                .into_boxed_slice(),
+            None,
+        );
+        let mut c = SourceMap::new(
+            None,
+            vec!["bar".into()],
+            None,
+            vec!["c.js".into()],
+            vec![],
+            vec![Token::new(0, 3, 1, 1, Some(0), Some(0))].into_boxed_slice(),
+            None,
+        );
+        c.set_x_google_ignore_list(vec![0]);
+        c.set_source_root("src");
+        c.set_debug_id("2bbf7d21-6d1a-4a6f-9d4b-3471e8a0c1f5");
+
+        let mut builder = crate::ConcatSourceMapBuilder::default();
+        builder.add_sourcemap(&a, 0);
+        builder.add_sourcemap(&b, 0);
+        builder.add_sourcemap(&c, 5);
+        let expected = builder.into_sourcemap();
+
+        let mut appended = a.clone();
+        appended.append(&b, 0);
+        appended.append(&c, 5);
+
+        assert_eq!(appended.token_count(), 4);
+        assert_eq!(appended.tokens, expected.tokens);
+        assert_eq!(appended.token_chunks, expected.token_chunks);
+        assert_eq!(appended.sources, expected.sources);
+        assert_eq!(appended.get_sources().collect::<Vec<_>>(), ["a.js", "src/c.js"]);
+        assert_eq!(appended.get_source_root(), None);
+        assert_eq!(appended.get_debug_id(), expected.get_debug_id());
+        assert_eq!(appended.names, expected.names);
+        assert_eq!(appended.source_contents, expected.source_contents);
+        assert_eq!(appended.get_x_google_ignore_list(), Some(&[1][..]));
+        assert_eq!(appended.to_json_string(), {
+            let mut expected = expected;
+            expected.set_x_google_ignore_list(vec![1]);
+            expected.to_json_string()
+        });
+
+        // A root on the receiving map is folded too, and differing debug ids are cleared.
+        let mut rooted = c.clone();
+        rooted.set_source_root("lib/");
+        rooted.set_debug_id("7a1c9f02-53e4-4b8d-8f16-0d9e2c4b6a37");
+        rooted.append(&c, 1);
+        assert_eq!(rooted.get_sources().collect::<Vec<_>>(), ["lib/c.js", "src/c.js"]);
+        assert_eq!(rooted.get_source_root(), None);
+        assert_eq!(rooted.get_debug_id(), None);
+    }
+
+    #[test]
+    fn resolved_source() {
+        let mut sm = SourceMap::default();
+        sm.set_sources(["a.js", "/abs.js", "https://x.com/b.js"]);
+        assert_eq!(sm.get_resolved_source(0).as_deref(), Some("a.js"));
+
+        sm.source_root = Some("src".into());
+        assert_eq!(sm.get_resolved_source(0).as_deref(), Some("src/a.js"));
+        assert_eq!(sm.get_resolved_source(1).as_deref(), Some("/abs.js"));
+        assert_eq!(sm.get_resolved_source(2).as_deref(), Some("https://x.com/b.js"));
+        assert_eq!(sm.get_resolved_source(3), None);
+
+        sm.source_root = Some("https://cdn/".into());
+        assert_eq!(sm.get_resolved_source(0).as_deref(), Some("https://cdn/a.js"));
+    }
+
+    #[test]
+    fn builder_matches_new() {
+        let tokens = vec![Token::new(0, 0, 0, 0, Some(0), Some(0))];
+        let built = SourceMap::builder()
+            .file("out.js")
+            .names(["x"])
+            .sources(["a.js", "b.js"])
+            .source_contents([Some("a"), None])
+            .tokens(tokens.clone())
+            .build();
+        let new = SourceMap::new(
+            Some("out.js".into()),
+            vec!["x".into()],
+            None,
+            vec!["a.js".into(), "b.js".into()],
+            vec![Some("a".into()), None],
+            tokens.into_boxed_slice(),
+            None,
+        );
+        assert_eq!(built.to_json_string(), new.to_json_string());
+        assert_eq!(built.tokens, new.tokens);
+    }
+
+    #[test]
+    fn map_tokens() {
+        let a = SourceMap::builder()
+            .names(["x"])
+            .sources(["a.js"])
+            .tokens(vec![
+                Token::new(0, 0, 0, 0, Some(0), Some(0)),
+                Token::new(0, 4, 0, 4, Some(0), None),
+            ])
+            .build();
+        let b = SourceMap::builder()
+            .sources(["b.js"])
+            .tokens(vec![Token::new(0, 10, 1, 0, Some(0), None)])
+            .build();
+        // `b` continues `a`'s line, so its chunk baseline holds a shifted column.
+        let mut sm =
+            crate::ConcatSourceMapBuilder::from_sourcemaps(&[(&a, 0), (&b, 0)]).into_sourcemap();
+
+        sm.map_tokens(|mut token| {
+            token.dst_col += 3;
+            token
+        });
+
+        let table = sm.generate_lookup_table();
+        assert_eq!(sm.lookup_token(&table, 0, 2), None);
+        assert_eq!(sm.lookup_token(&table, 0, 3), Some(Token::new(0, 3, 0, 0, Some(0), Some(0))));
+        assert_eq!(sm.lookup_token(&table, 0, 8), Some(Token::new(0, 7, 0, 4, Some(0), None)));
+        assert_eq!(sm.lookup_token(&table, 0, 13), Some(Token::new(0, 13, 1, 0, Some(1), None)));
+
+        // The chunked encoding agrees with encoding the tokens in one run.
+        let mut unchunked = sm.clone();
+        unchunked.token_chunks = None;
+        assert_eq!(sm.to_json_string(), unchunked.to_json_string());
+    }
+
+    #[test]
+    fn drop_ignored_tokens() {
+        let mut sm = SourceMap::builder()
+            .sources(["app.js", "node_modules/lib.js"])
+            .tokens(vec![
+                Token::new(0, 0, 0, 0, Some(0), None),
+                Token::new(0, 5, 3, 0, Some(1), None),
+                Token::new(1, 0, 0, 0, None, None),
+                Token::new(1, 4, 1, 0, Some(0), None),
+            ])
+            .token_chunks(vec![TokenChunk::new(0, 4, 0, 0, 0, 0, 0, 0)])
+            .build();
+
+        // Nothing is ignore-listed yet.
+        sm.drop_ignored_tokens();
+        assert_eq!(sm.token_count(), 4);
+
+        sm.set_x_google_ignore_list(vec![1]);
+        sm.drop_ignored_tokens();
+        assert_eq!(
+            sm.get_tokens().collect::<Vec<_>>(),
+            [
+                Token::new(0, 0, 0, 0, Some(0), None),
+                Token::new(1, 0, 0, 0, None, None),
+                Token::new(1, 4, 1, 0, Some(0), None),
+            ]
+        );
+        assert!(sm.token_chunks.is_none());
+        assert_eq!(sm.source_count(), 2);
+    }
+
+    #[test]
+    fn is_source_ignored() {
+        let mut sm = SourceMap::builder()
+            .sources(["lib.js", "app.js"])
+            .tokens(vec![
+                Token::new(0, 0, 0, 0, Some(0), None),
+                Token::new(0, 4, 0, 0, Some(1), None),
+                Token::new(0, 8, 0, 0, None, None),
+            ])
+            .build();
+        assert!(!sm.is_source_ignored(0));
+
+        sm.set_x_google_ignore_list(vec![0]);
+        assert!(sm.is_source_ignored(0));
+        assert!(!sm.is_source_ignored(1));
+        let ignored: Vec<bool> = sm.get_source_view_tokens().map(|t| t.is_ignored()).collect();
+        assert_eq!(ignored, [true, false, false]);
+    }
+
+    #[test]
+    fn canonicalize() {
+        let first = Token::new(0, 0, 0, 0, Some(0), None);
+        // Generated-only tokens, so `b`'s copies match `a`'s after concat renumbering.
+        let boundary = Token::new(1, 0, 0, 0, None, None);
+        let last = Token::new(2, 0, 0, 0, None, None);
+
+        // The concat builder drops the second map's first token as a boundary duplicate.
+        let a = SourceMap::builder().sources(["a.js"]).tokens(vec![first, boundary]).build();
+        let b = SourceMap::builder().tokens(vec![boundary, last]).build();
+        let mut concat =
+            crate::ConcatSourceMapBuilder::from_sourcemaps(&[(&a, 0), (&b, 0)]).into_sourcemap();
+        // Built directly, the duplicate survives and tokens arrive out of order.
+        let mut built = SourceMap::builder()
+            .sources(["a.js"])
+            .tokens(vec![boundary, first, boundary, last])
+            .build();
+        assert_ne!(concat.tokens, built.tokens);
+
+        concat.canonicalize();
+        built.canonicalize();
+        assert_eq!(built.tokens, concat.tokens);
+        assert_eq!(built.to_json_string(), concat.to_json_string());
+    }
+
+    #[test]
+    fn try_push_token() {
+        let mut sm = SourceMap::builder().names(["x"]).sources(["a.js"]).build();
+        sm.try_push_token(Token::new(0, 0, 0, 0, Some(0), Some(0))).unwrap();
+        sm.try_push_token(Token::new(0, 4, 0, 0, None, None)).unwrap();
+        assert!(matches!(
+            sm.try_push_token(Token::new(1, 0, 0, 0, Some(1), None)),
+            Err(Error::BadSourceReference(1))
+        ));
+        assert!(matches!(
+            sm.try_push_token(Token::new(1, 0, 0, 0, Some(0), Some(3))),
+            Err(Error::BadNameReference(3))
+        ));
+        assert_eq!(sm.token_count(), 2);
+
+        // Pushed tokens stay covered by `token_chunks`, even when there were none yet.
+        let mut chunked = crate::ConcatSourceMapBuilder::default().into_sourcemap();
+        chunked.set_sources(["a.js"]);
+        chunked.try_push_token(Token::new(0, 0, 0, 0, Some(0), None)).unwrap();
+        chunked.try_push_token(Token::new(1, 0, 0, 0, Some(0), None)).unwrap();
+        assert_eq!(
+            chunked.to_json_string(),
+            r#"{"version":3,"names":[],"sources":["a.js"],"mappings":"AAAA;AAAA"}"#
+        );
+        assert_eq!(
+            sm.to_json_string(),
+            r#"{"version":3,"names":["x"],"sources":["a.js"],"mappings":"AAAAA,I"}"#
+        );
+    }
+
+    #[test]
+    fn shared() {
+        let sm = SourceMap::builder()
+            .sources(["a.js"])
+            .tokens(vec![Token::new(0, 0, 0, 0, Some(0), None)])
+            .build();
+        let shared = sm.shared();
+        let handle = std::sync::Arc::clone(&shared);
+        // Both handles see the very same token array.
+        assert!(std::ptr::eq(shared.tokens.as_ptr(), handle.tokens.as_ptr()));
+
+        let table = handle.generate_lookup_table();
+        let lookup = std::thread::scope(|scope| {
+            scope.spawn(|| handle.lookup_token(&table, 0, 3)).join().unwrap()
+        });
+        assert_eq!(lookup, shared.get_token(0));
+    }
+
+    #[test]
+    fn rechunk() {
+        let json = include_str!("../tests/fixtures/esbuild/example.js.map");
+        let mut sm = SourceMap::from_json_string(json).unwrap();
+        let expected = sm.to_json_string();
+
+        for target in [0, 1, 2, 4, 7, usize::MAX] {
+            sm.rechunk(target);
+            let chunks = sm.token_chunks.as_deref().unwrap();
+            assert!(chunks.len() <= target.max(1), "{target}");
+            assert_eq!(chunks.first().unwrap().start, 0);
+            assert_eq!(chunks.last().unwrap().end as usize, sm.token_count());
+            for pair in chunks.windows(2) {
+                assert_eq!(pair[0].end, pair[1].start);
+                let start = pair[1].start as usize;
+                assert_ne!(sm.tokens[start - 1].dst_line, sm.tokens[start].dst_line);
+            }
+            assert_eq!(sm.to_json_string(), expected, "{target}");
+        }
+        sm.rechunk(4);
+        assert!(sm.token_chunks.as_ref().unwrap().len() > 1);
+
+        let mut empty = SourceMap::default();
+        empty.rechunk(4);
+        assert_eq!(empty.to_json_string(), SourceMap::default().to_json_string());
+    }
+
+    #[test]
+    fn memory_usage() {
+        let mut sm = SourceMap::builder().sources(["a.js"]).source_contents([Some("abc")]).build();
+        let before = sm.memory_usage();
+        assert!(before >= size_of::<SourceMap>() + "a.js".len() + "abc".len());
+
+        sm.try_push_token(Token::new(0, 0, 0, 0, Some(0), None)).unwrap();
+        sm.try_push_token(Token::new(1, 0, 0, 0, Some(0), None)).unwrap();
+        assert_eq!(sm.memory_usage(), before + 2 * size_of::<Token>());
+    }
+
+    #[test]
+    fn source_line_starts() {
+        let sm = SourceMap::builder()
+            .sources(["a.js", "b.js"])
+            .source_contents([Some("a\r\nb\u{2028}c\rd\u{2029}\ne"), None])
+            .build();
+        let starts = sm.source_line_starts(0).unwrap();
+        assert_eq!(starts, [0, 3, 7, 9, 13, 14]);
+        let content = sm.get_source_content(0).unwrap();
+        assert_eq!(&content[starts[2] as usize..starts[3] as usize], "c\r");
+        assert_eq!(sm.source_line_starts(1), None);
+        assert_eq!(line_starts(""), [0]);
+    }
+
+    #[test]
+    fn source_byte_offset() {
+        // `é` is 2 UTF-8 bytes and 1 UTF-16 unit; `👀` is 4 bytes and 2 units.
+        let sm = SourceMap::builder()
+            .sources(["a.js", "b.js"])
+            .source_contents([Some("let é = 1;\r\n👀x\n"), None])
+            .build();
+        assert_eq!(sm.source_byte_offset(0, 0, 0), Some(0));
+        assert_eq!(sm.source_byte_offset(0, 0, 5), Some(6));
+        assert_eq!(sm.source_byte_offset(0, 0, 10), Some(11));
+        assert_eq!(sm.source_byte_offset(0, 0, 11), None);
+        assert_eq!(sm.source_byte_offset(0, 1, 2), Some(17));
+        // Inside the surrogate pair of `👀`.
+        assert_eq!(sm.source_byte_offset(0, 1, 1), None);
+        assert_eq!(sm.source_byte_offset(0, 2, 0), Some(19));
+        assert_eq!(sm.source_byte_offset(0, 3, 0), None);
+        assert_eq!(sm.source_byte_offset(1, 0, 0), None);
+
+        // The cached line starts follow content changes.
+        let mut sm = sm;
+        sm.load_source_contents(|_| Some("b\nbb".to_owned()));
+        assert_eq!(sm.source_byte_offset(1, 1, 2), Some(4));
+        sm.set_source_contents(vec![Some("x\ny"), Some("z")]);
+        assert_eq!(sm.source_byte_offset(0, 1, 0), Some(2));
+        assert_eq!(sm.source_byte_offset(1, 1, 0), None);
+        assert_eq!(sm.add_source_and_content("c.js", Some("\n\nc")), 2);
+        assert_eq!(sm.source_byte_offset(2, 2, 1), Some(3));
+        sm.clear_source_contents();
+        assert_eq!(sm.source_byte_offset(0, 0, 0), None);
+    }
+
+    #[test]
+    fn source_mapping_url_comments() {
+        assert_eq!(SourceMap::external_comment("out.js.map"), "//# sourceMappingURL=out.js.map");
+
+        let sm = SourceMap::builder()
+            .sources(["a.js"])
+            .tokens(vec![Token::new(0, 0, 0, 0, Some(0), None)])
+            .build();
+        let comment = sm.inline_comment();
+        let base64 = comment
+            .strip_prefix("//# sourceMappingURL=data:application/json;charset=utf-8;base64,")
+            .unwrap();
+        let json = base64_simd::STANDARD.decode_to_vec(base64).unwrap();
+        let decoded = SourceMap::from_json_slice(&json).unwrap();
+        assert_eq!(decoded.to_json_string(), sm.to_json_string());
+    }
+
+    #[test]
+    fn add_name_and_source() {
+        let mut sm = SourceMap::from_json_string(
+            r#"{"version":3,"names":["x"],"sources":["a.js","b.js"],"sourcesContent":["a"],"mappings":""}"#,
+        )
+        .unwrap();
+        assert_eq!(sm.add_name("x"), 0);
+        assert_eq!(sm.add_name("helper"), 1);
+        assert_eq!(sm.add_name("helper"), 1);
+        assert_eq!(sm.get_names().collect::<Vec<_>>(), ["x", "helper"]);
+
+        assert_eq!(sm.add_source_and_content("b.js", Some("ignored")), 1);
+        assert_eq!(sm.add_source_and_content("helpers.js", Some("h")), 2);
+        assert_eq!(sm.add_source_and_content("empty.js", None), 3);
+        assert_eq!(
+            sm.get_source_contents().collect::<Vec<_>>(),
+            [Some("a"), None, Some("h"), None]
+        );
+        assert_eq!(sm.name_index("helper"), Some(1));
+        assert_eq!(sm.source_index("helpers.js"), Some(2));
+        assert_eq!(sm.source_index("empty.js"), Some(3));
+    }
+
+    #[test]
+    fn name_and_source_index_follow_mutations() {
+        let mut sm = SourceMap::builder().sources(["a.js", "a.js", "b.js"]).names(["x"]).build();
+        assert_eq!(sm.source_index("a.js"), Some(0));
+        assert_eq!(sm.name_index("x"), Some(0));
+
+        sm.dedupe_sources();
+        assert_eq!(sm.source_index("b.js"), Some(1));
+        assert!(sm.rename_source_by_path("b.js", "c.js"));
+        assert_eq!(sm.source_index("b.js"), None);
+        assert_eq!(sm.source_index("c.js"), Some(1));
+        sm.set_sources(["d.js"]);
+        assert_eq!(sm.source_index("a.js"), None);
+        assert_eq!(sm.source_index("d.js"), Some(0));
+
+        sm.append(&SourceMap::builder().sources(["e.js"]).names(["y"]).build(), 0);
+        assert_eq!(sm.source_index("e.js"), Some(1));
+        assert_eq!(sm.name_index("y"), Some(1));
+    }
+
+    #[test]
+    fn token_ends() {
+        let ranged = TokenEnd { dst_end_col: Some(9), src_end_col: Some(12) };
+        let mut sm = SourceMap::builder()
+            .sources(["a.js"])
+            .tokens(vec![Token::new(0, 4, 0, 6, Some(0), None), Token::new(1, 0, 1, 0, None, None)])
+            .token_ends(vec![ranged, TokenEnd::default()])
+            .build();
+        assert_eq!((sm.get_dst_end_col(0), sm.get_src_end_col(0)), (Some(9), Some(12)));
+        assert_eq!((sm.get_dst_end_col(1), sm.get_src_end_col(1)), (None, None));
+        assert_eq!(sm.get_dst_end_col(2), None);
+        // Standard v3 output, so the ends do not survive a round trip.
+        let json = sm.to_json_string();
+        assert_eq!(SourceMap::from_json_string(&json).unwrap().get_dst_end_col(0), None);
+
+        sm.try_push_token(Token::new(2, 0, 2, 0, Some(0), None)).unwrap();
+        assert_eq!(sm.get_dst_end_col(0), Some(9));
+        assert_eq!(sm.token_ends.as_ref().map(|ends| ends.len()), Some(3));
+        sm.retain_dst_range(0..1);
+        assert_eq!(sm.get_dst_end_col(0), None);
+
+        sm.set_token_ends(vec![ranged]);
+        assert_eq!(sm.get_src_end_col(0), Some(12));
+        // `SourceMapParts` has no end columns.
+        assert_eq!(SourceMap::from_parts(sm.clone().into_parts()).get_src_end_col(0), None);
+        sm.clear_token_ends();
+        assert_eq!(sm.get_src_end_col(0), None);
+    }
+
+    #[test]
+    fn retain_tokens() {
+        let mut sm = SourceMap::from_json_string(
+            r#"{"version":3,"names":[],"sources":["a.js"],"mappings":"AAAA,C,EAAE;A,CAAC"}"#,
+        )
+        .unwrap();
+        sm.rechunk(2);
+        sm.retain_tokens(|_| true);
+        assert!(sm.token_chunks.is_some());
+
+        sm.retain_tokens(|token| token.get_source_id().is_some());
+        assert_eq!(sm.token_count(), 3);
+        assert!(sm.token_chunks.is_none());
+        assert_eq!(
+            sm.to_json_string(),
+            r#"{"version":3,"names":[],"sources":["a.js"],"mappings":"AAAA,GAAE;CAAC"}"#
+        );
+    }
+
+    #[test]
+    fn clear_optional_fields() {
+        let mut sm = SourceMap::from_json_string(
+            r#"{"version":3,"file":"out.js","sourceRoot":"src/","names":[],"sources":["a.js"],"mappings":"AAAA","debugId":"56431d54-c0a6-451d-8ea2-ba5de5d8ca2e"}"#,
+        )
+        .unwrap();
+        sm.set_file("bundle.js");
+        sm.clear_file();
+        sm.clear_source_root();
+        sm.clear_debug_id();
+        assert_eq!(sm.get_file(), None);
+        assert_eq!(sm.get_source_root(), None);
+        assert_eq!(sm.get_debug_id(), None);
+        assert_eq!(
+            sm.to_json_string(),
+            r#"{"version":3,"names":[],"sources":["a.js"],"mappings":"AAAA"}"#
+        );
+    }
+
+    #[test]
+    fn sparse_lookup_table() {
+        let sm = SourceMap::builder()
+            .sources(["a.js"])
+            .tokens(vec![
+                Token::new(2, 0, 0, 0, Some(0), None),
+                Token::new(2, 5, 0, 5, Some(0), None),
+                Token::new(1_000_000, 3, 1, 0, Some(0), None),
+            ])
+            .build();
+        let sparse = sm.generate_sparse_lookup_table();
+        assert_eq!(sparse.len(), 2);
+        assert_eq!(sm.generate_lookup_table().len(), 1_000_001);
+
+        let dense = sm.generate_lookup_table();
+        for (line, col) in [(0, 0), (2, 0), (2, 4), (2, 9), (3, 0), (1_000_000, 2), (1_000_000, 3)]
+        {
+            assert_eq!(sparse.lookup_token(line, col), sm.lookup_token(&dense, line, col));
+        }
+        assert_eq!(sparse.tokens_on_line(2).len(), 2);
+        assert!(SourceMap::default().generate_sparse_lookup_table().is_empty());
+    }
+
+    #[test]
+    fn is_sorted() {
+        let sorted = SourceMap::builder()
+            .tokens(vec![Token::new(0, 4, 0, 0, None, None), Token::new(1, 0, 0, 0, None, None)])
+            .build();
+        assert!(sorted.is_sorted());
+        assert!(SourceMap::default().is_sorted());
+
+        let mut unsorted = SourceMap::builder()
+            .tokens(vec![Token::new(1, 0, 0, 0, None, None), Token::new(0, 4, 0, 0, None, None)])
+            .build();
+        assert!(!unsorted.is_sorted());
+        unsorted.canonicalize();
+        assert!(unsorted.is_sorted());
+
+        // A negative column delta decodes fine, and building a lookup table must not panic.
+        let decoded = SourceMap::from_json_string(
+            r#"{"version":3,"names":[],"sources":["a.js"],"mappings":"EAAA,DAAA"}"#,
+        )
+        .unwrap();
+        assert!(!decoded.is_sorted());
+        assert_eq!(decoded.generate_lookup_table().len(), 1);
+    }
+
+    #[test]
+    fn eq_ignores_token_chunks() {
+        let input = r#"{"version":3,"file":"out.js","names":["x"],"sources":["a.js","b.js"],"sourcesContent":["a"],"mappings":"AAAAA;ACAA,EAAE","debugId":"56431d54-c0a6-451d-8ea2-ba5de5d8ca2e"}"#;
+        let sm = SourceMap::from_json_string(input).unwrap();
+        let roundtrip = SourceMap::from_json(sm.to_json()).unwrap();
+        assert_eq!(sm, roundtrip);
+
+        let mut rechunked = sm.clone().into_owned();
+        rechunked.rechunk(2);
+        assert!(rechunked.token_chunks.is_some());
+        assert_eq!(sm, rechunked);
+
+        let mut renamed = sm.clone();
+        renamed.set_file("other.js");
+        assert_ne!(sm, renamed);
+    }
+
+    #[test]
+    fn reverse_lookup() {
+        let sm = SourceMap::builder()
+            .sources(["a.js", "b.js"])
+            .tokens(vec![
+                Token::new(0, 0, 3, 0, Some(1), None),
+                Token::new(0, 6, 0, 4, Some(0), None),
+                Token::new(1, 0, 0, 0, Some(0), None),
+                Token::new(1, 2, 0, 0, None, None),
+                Token::new(2, 0, 0, 4, Some(0), None),
+            ])
+            .build();
+        assert!(!sm.is_sorted_by_source());
+        let table = sm.build_reverse_lookup();
+
+        let generated = |source_id, line, col| {
+            table
+                .lookup_generated_position(source_id, line, col)
+                .map(|token| (token.get_dst_line(), token.get_dst_col()))
+        };
+        assert_eq!(generated(0, 0, 0), Some((1, 0)));
+        assert_eq!(generated(0, 0, 3), Some((1, 0)));
+        // Two tokens map from a.js 0:4; the first in generated order wins.
+        assert_eq!(generated(0, 0, 9), Some((0, 6)));
+        assert_eq!(generated(1, 3, 7), Some((0, 0)));
+        assert_eq!(generated(1, 2, 0), None);
+        assert_eq!(generated(1, 4, 0), None);
+        assert_eq!(generated(2, 0, 0), None);
+
+        let in_order = SourceMap::builder()
+            .tokens(vec![
+                Token::new(0, 0, 0, 0, Some(0), None),
+                Token::new(1, 0, 2, 0, Some(0), None),
+            ])
+            .build();
+        assert!(in_order.is_sorted_by_source());
+    }
+
+    #[test]
+    fn retain_dst_range() {
+        let mut sm = SourceMap::builder()
+            .sources(["a.js"])
+            .tokens(
+                (0..5).map(|line| Token::new(line, 1, line, 0, Some(0), None)).collect::<Vec<_>>(),
+            )
+            .token_chunks(vec![TokenChunk::new(0, 5, 0, 0, 0, 0, 0, 0)])
+            .build();
+        sm.retain_dst_range(1..3);
+        assert_eq!(
+            sm.get_tokens().collect::<Vec<_>>(),
+            [Token::new(1, 1, 1, 0, Some(0), None), Token::new(2, 1, 2, 0, Some(0), None)]
+        );
+        assert!(sm.token_chunks.is_none());
+    }
+
+    #[test]
+    fn from_str() {
+        let sm: SourceMap =
+            r#"{"version":3,"names":[],"sources":["a.js"],"mappings":"AAAA"}"#.parse().unwrap();
+        assert_eq!(sm.get_source(0), Some("a.js"));
+        assert!("{".parse::<SourceMap>().is_err());
+    }
+
+    #[test]
+    fn try_from() {
+        let input = r#"{"version":3,"names":[],"sources":["a.js"],"mappings":"AAAA"}"#;
+        let json: JSONSourceMap = serde_json::from_str(input).unwrap();
+        let sm: SourceMap = json.try_into().unwrap();
+        assert_eq!(sm.get_source(0), Some("a.js"));
+
+        let sm = SourceMap::try_from(input.as_bytes()).unwrap();
+        assert_eq!(sm.get_tokens().count(), 1);
+        assert!(SourceMap::try_from(&b"{"[..]).is_err());
+    }
+
+    #[test]
+    fn encode_mappings_range() {
+        let sm = SourceMap::builder()
+            .sources(["a.js"])
+            .tokens(vec![
+                Token::new(0, 0, 0, 0, Some(0), None),
+                Token::new(1, 2, 4, 1, Some(0), None),
+                Token::new(1, 6, 4, 5, Some(0), None),
+                Token::new(3, 0, 9, 0, Some(0), None),
+            ])
+            .build();
+        let mappings = sm.encode_mappings_range(1, 2);
+        let json =
+            format!(r#"{{"version":3,"names":[],"sources":["a.js"],"mappings":"{mappings}"}}"#);
+        assert_eq!(
+            SourceMap::from_json_string(&json).unwrap().get_tokens().collect::<Vec<_>>(),
+            [Token::new(0, 2, 4, 1, Some(0), None), Token::new(0, 6, 4, 5, Some(0), None)]
+        );
+        assert_eq!(sm.encode_mappings_range(2, 3), "");
+        assert_eq!(sm.encode_mappings_range(3, 1), "");
+    }
+
+    #[test]
+    fn try_new() {
+        let sources = || vec![Cow::Borrowed("a.js")];
+        let names = || vec![Cow::Borrowed("x")];
+        let tokens = |source_id, name_id| {
+            vec![Token::new(0, 0, 0, 0, Some(0), None), Token::new(0, 1, 0, 0, source_id, name_id)]
+                .into_boxed_slice()
+        };
+        let sm = SourceMap::try_new(
+            None,
+            names(),
+            None,
+            sources(),
+            vec![],
+            tokens(Some(0), Some(0)),
+            None,
+        );
+        assert_eq!(sm.unwrap().get_tokens().count(), 2);
+        let sm =
+            SourceMap::try_new(None, names(), None, sources(), vec![], tokens(None, None), None);
+        assert!(sm.is_ok());
+
+        let err =
+            SourceMap::try_new(None, names(), None, sources(), vec![], tokens(Some(1), None), None);
+        assert!(matches!(err, Err(Error::BadSourceReference(1))));
+        let err = SourceMap::try_new(
+            None,
+            names(),
+            None,
+            sources(),
+            vec![],
+            tokens(Some(0), Some(3)),
+            None,
+        );
+        assert!(matches!(err, Err(Error::BadNameReference(3))));
+    }
+
+    #[test]
+    fn sources_resolved() {
+        let mut sm = SourceMap::builder().source_root("src").sources(["a.js", "lib/b.js"]).build();
+        assert_eq!(sm.sources_resolved().collect::<Vec<_>>(), ["src/a.js", "src/lib/b.js"]);
+        sm.clear_source_root();
+        assert_eq!(sm.sources_resolved().collect::<Vec<_>>(), ["a.js", "lib/b.js"]);
+    }
+
+    #[test]
+    fn compact() {
+        let a = Token::new(0, 0, 0, 0, Some(0), None);
+        let b = Token::new(0, 3, 0, 3, Some(0), None);
+        let mut sm = SourceMap::builder().sources(["a.js"]).tokens(vec![a, a, b, a, b, b]).build();
+        let encoded = sm.to_json_string();
+        sm.compact();
+        assert_eq!(sm.get_tokens().collect::<Vec<_>>(), [a, b, a, b]);
+        assert!(sm.to_json_string().len() < encoded.len());
+    }
+
+    #[test]
+    fn binary_search_dst() {
+        let sm = SourceMap::builder()
+            .tokens(vec![
+                Token::new(0, 0, 0, 0, None, None),
+                Token::new(0, 5, 0, 0, None, None),
+                Token::new(2, 1, 0, 0, None, None),
+            ])
+            .build();
+        assert_eq!(sm.binary_search_dst(0, 5), Ok(1));
+        assert_eq!(sm.binary_search_dst(0, 3), Err(1));
+        assert_eq!(sm.binary_search_dst(1, 0), Err(2));
+        assert_eq!(sm.binary_search_dst(9, 0), Err(3));
+        assert_eq!(SourceMap::default().binary_search_dst(0, 0), Err(0));
+    }
+
+    #[test]
+    fn set_source_root() {
+        let mut sm = SourceMap::builder().sources(["x.js"]).build();
+        for root in ["a", "a/", "a//"] {
+            sm.set_source_root(root);
+            assert_eq!(sm.get_source_root(), Some("a/"));
+            assert_eq!(sm.get_resolved_source(0).as_deref(), Some("a/x.js"));
+        }
+        sm.set_source_root("/");
+        assert_eq!(sm.get_resolved_source(0).as_deref(), Some("/x.js"));
+        sm.set_source_root("");
+        assert_eq!(sm.get_source_root(), Some(""));
+        assert_eq!(sm.get_resolved_source(0).as_deref(), Some("x.js"));
+    }
+
+    #[test]
+    fn assert_monotonic_dst() {
+        let token = |line, col| Token::new(line, col, 0, 0, None, None);
+        let sm = SourceMap::builder().tokens(vec![token(0, 0), token(0, 0), token(1, 2)]).build();
+        assert!(sm.assert_monotonic_dst().is_ok());
+        assert!(SourceMap::default().assert_monotonic_dst().is_ok());
+
+        let sm = SourceMap::builder()
+            .tokens(vec![token(0, 0), token(1, 2), token(1, 1), token(0, 9)])
+            .build();
+        assert!(matches!(sm.assert_monotonic_dst(), Err(Error::UnsortedToken(2))));
+    }
+
+    #[test]
+    fn rename_source_by_path() {
+        let mut sm = SourceMap::builder().sources(["coolstuff.js", "other.js"]).build();
+        assert!(sm.rename_source_by_path("coolstuff.js", "renamed.js"));
+        assert_eq!(sm.get_sources().collect::<Vec<_>>(), ["renamed.js", "other.js"]);
+        assert!(!sm.rename_source_by_path("coolstuff.js", "again.js"));
+        assert_eq!(sm.get_sources().collect::<Vec<_>>(), ["renamed.js", "other.js"]);
+    }
+
+    #[test]
+    fn concat() {
+        let a = SourceMap::builder()
+            .sources(["a.js"])
+            .names(["x"])
+            .tokens(vec![Token::new(0, 0, 0, 0, Some(0), Some(0))])
+            .build();
+        let b = SourceMap::builder()
+            .sources(["b.js"])
+            .tokens(vec![Token::new(0, 2, 1, 0, Some(0), None)])
+            .build();
+        let maps = [(&a, 0), (&b, 1)];
+        let sm = SourceMap::concat(&maps);
+        assert_eq!(sm, ConcatSourceMapBuilder::from_sourcemaps(&maps).into_sourcemap());
+        assert_eq!(
+            sm.get_tokens().collect::<Vec<_>>(),
+            [Token::new(0, 0, 0, 0, Some(0), Some(0)), Token::new(1, 2, 1, 0, Some(1), None)]
+        );
+    }
+
+    #[test]
+    fn get_tokens_from() {
+        let sm = SourceMap::builder()
+            .tokens((0..5).map(|col| Token::new(0, col, 0, col, None, None)).collect::<Vec<_>>())
+            .build();
+        assert_eq!(
+            sm.get_tokens_from(2).collect::<Vec<_>>(),
+            sm.get_tokens().skip(2).collect::<Vec<_>>()
+        );
+        assert_eq!(sm.get_tokens_from(2).len(), 3);
+        assert_eq!(sm.get_tokens_from(5).len(), 0);
+        assert_eq!(sm.get_tokens_from(9).len(), 0);
+    }
+
+    #[test]
+    fn set_tokens_from_iter() {
+        let mut sm = SourceMap::builder()
+            .sources(["a.js"])
+            .tokens(
+                (0..4).map(|line| Token::new(line, 0, line, 0, Some(0), None)).collect::<Vec<_>>(),
+            )
+            .token_chunks(vec![TokenChunk::new(0, 4, 0, 0, 0, 0, 0, 0)])
+            .build();
+        let odd: Vec<Token> =
+            sm.get_tokens().filter(|token| token.get_dst_line() % 2 == 1).collect();
+        sm.set_tokens_from_iter(odd.clone());
+        assert_eq!(sm.get_tokens().collect::<Vec<_>>(), odd);
+        assert!(sm.token_chunks.is_none());
+        assert_eq!(
+            sm.to_json_string(),
+            r#"{"version":3,"names":[],"sources":["a.js"],"mappings":";AACA;;AAEA"}"#
+        );
+    }
 }
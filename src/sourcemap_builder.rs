@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxBuildHasher, FxHashMap};
 
 use crate::{
     SourceMap,
@@ -20,6 +20,7 @@ use crate::{
 #[derive(Debug, Default)]
 pub struct SourceMapBuilder<'a> {
     pub(crate) file: Option<&'a str>,
+    pub(crate) source_root: Option<&'a str>,
     pub(crate) names_map: FxHashMap<&'a str, u32>,
     pub(crate) names: Vec<&'a str>,
     pub(crate) sources: Vec<&'a str>,
@@ -30,6 +31,20 @@ pub struct SourceMapBuilder<'a> {
 }
 
 impl<'a> SourceMapBuilder<'a> {
+    /// Create a builder with room for the given numbers of tokens, sources and names, so adding
+    /// up to that many reallocates nothing.
+    pub fn with_capacity(tokens: usize, sources: usize, names: usize) -> Self {
+        Self {
+            names_map: FxHashMap::with_capacity_and_hasher(names, FxBuildHasher),
+            names: Vec::with_capacity(names),
+            sources: Vec::with_capacity(sources),
+            sources_map: FxHashMap::with_capacity_and_hasher(sources, FxBuildHasher),
+            source_contents: Vec::with_capacity(sources),
+            tokens: Vec::with_capacity(tokens),
+            ..Self::default()
+        }
+    }
+
     /// Add a name, deduplicating. The name is borrowed for `'a` (no allocation).
     pub fn add_name(&mut self, name: &'a str) -> u32 {
         if let Some(&id) = self.names_map.get(name) {
@@ -83,6 +98,11 @@ impl<'a> SourceMapBuilder<'a> {
         self.file = Some(file);
     }
 
+    /// Set the `sourceRoot` prepended to every source path. Borrowed for `'a` (no allocation).
+    pub fn set_source_root(&mut self, source_root: &'a str) {
+        self.source_root = Some(source_root);
+    }
+
     /// Set the `SourceMap::token_chunks` to make the sourcemap to vlq mapping at parallel.
     pub fn set_token_chunks(&mut self, token_chunks: Vec<TokenChunk>) {
         self.token_chunks = Some(token_chunks);
@@ -103,7 +123,7 @@ impl<'a> SourceMapBuilder<'a> {
         SourceMap::new(
             self.file.map(Cow::Borrowed),
             self.names.into_iter().map(Cow::Borrowed).collect(),
-            None,
+            self.source_root.map(Cow::Borrowed),
             self.sources.into_iter().map(Cow::Borrowed).collect(),
             self.source_contents.into_iter().map(|content| content.map(Cow::Borrowed)).collect(),
             self.tokens.into_boxed_slice(),
@@ -124,7 +144,7 @@ impl<'a> SourceMapBuilder<'a> {
         crate::OwnedSourceMap::new(SourceMap::new(
             self.file.map(|file| Cow::Owned(file.to_owned())),
             self.names.into_iter().map(|name| Cow::Owned(name.to_owned())).collect(),
-            None,
+            self.source_root.map(|source_root| Cow::Owned(source_root.to_owned())),
             self.sources.into_iter().map(|source| Cow::Owned(source.to_owned())).collect(),
             self.source_contents
                 .into_iter()
@@ -205,8 +225,11 @@ mod tests {
         builder.add_token(0, 0, 0, 0, Some(source_id), Some(name_id));
         builder.set_token_chunks(vec![TokenChunk::new(0, 1, 0, 0, 0, 0, 0, 0)]);
 
+        builder.set_source_root("root/");
+
         let owned = builder.into_owned_sourcemap();
         assert_eq!(owned.get_file(), Some("f.js"));
+        assert_eq!(owned.get_source_root(), Some("root/"));
         assert_eq!(owned.get_name(0), Some("n"));
         assert_eq!(owned.get_source(0), Some("s.js"));
         assert_eq!(owned.get_source_content(0), Some("src"));
@@ -220,4 +243,50 @@ mod tests {
         assert_eq!(owned.get_tokens().count(), 0);
         assert!(owned.as_source_map().token_chunks.is_none());
     }
+
+    #[test]
+    fn source_root() {
+        let mut builder = SourceMapBuilder::default();
+        builder.set_source_root("src/");
+        builder.set_source_and_content("a.js", "");
+        let sm = builder.into_sourcemap();
+        assert_eq!(sm.get_source_root(), Some("src/"));
+        assert!(sm.to_json_string().contains(r#""sourceRoot":"src/""#));
+    }
+
+    #[test]
+    fn names_without_sources_roundtrip() {
+        // Valid but rare: names, no file and no sources. Tokens without a source can only encode
+        // their generated column, so they carry no name either.
+        let mut builder = SourceMapBuilder::default();
+        builder.add_name("x");
+        builder.add_name("y");
+        builder.add_token(0, 0, 0, 0, None, None);
+        builder.add_token(1, 4, 0, 0, None, None);
+        let sm = builder.into_sourcemap();
+
+        let json = sm.to_json_string();
+        assert_eq!(json, r#"{"version":3,"names":["x","y"],"sources":[],"mappings":"A;I"}"#);
+        let decoded = SourceMap::from_json_string(&json).unwrap();
+        assert_eq!(decoded.get_file(), None);
+        assert_eq!(decoded.get_names().collect::<Vec<_>>(), vec!["x", "y"]);
+        assert_eq!(decoded.get_sources().count(), 0);
+        assert_eq!(decoded.get_tokens().collect::<Vec<_>>(), sm.get_tokens().collect::<Vec<_>>());
+        assert_eq!(decoded.to_json_string(), json);
+    }
+
+    #[test]
+    fn with_capacity() {
+        let mut builder = SourceMapBuilder::with_capacity(100, 3, 5);
+        assert!(builder.tokens.capacity() >= 100);
+        assert!(builder.sources.capacity() >= 3 && builder.source_contents.capacity() >= 3);
+        assert!(builder.sources_map.capacity() >= 3);
+        assert!(builder.names.capacity() >= 5 && builder.names_map.capacity() >= 5);
+
+        let tokens = builder.tokens.as_ptr();
+        for i in 0..100 {
+            builder.add_token(0, i, 0, i, None, None);
+        }
+        assert_eq!(builder.tokens.as_ptr(), tokens);
+    }
 }
@@ -0,0 +1,122 @@
+//! Conversions to and from [`sourcemap::SourceMap`], behind the `sourcemap-compat` feature.
+//!
+//! The data is copied field by field, without a round-trip through JSON.
+
+use std::{borrow::Cow, sync::Arc};
+
+use crate::{SourceMap, Token, token::INVALID_ID};
+
+impl SourceMap<'_> {
+    /// Copy a [`sourcemap::SourceMap`] into a new map.
+    ///
+    /// `sourcemap` only exposes sources already joined with its `sourceRoot`, so the result has
+    /// the resolved paths as `sources` and no `sourceRoot`.
+    pub fn from_sourcemap_crate(sm: &sourcemap::SourceMap) -> SourceMap<'static> {
+        let owned = |s: &str| Cow::Owned(s.to_owned());
+        let tokens = sm
+            .tokens()
+            .map(|token| {
+                // `sourcemap` marks missing ids with `u32::MAX` too.
+                let raw = token.get_raw_token();
+                Token::from_raw(
+                    raw.dst_line,
+                    raw.dst_col,
+                    raw.src_line,
+                    raw.src_col,
+                    raw.src_id,
+                    raw.name_id,
+                )
+            })
+            .collect();
+        let mut map = SourceMap::new(
+            sm.get_file().map(owned),
+            sm.names().map(owned).collect(),
+            None,
+            sm.sources().map(owned).collect(),
+            (0..sm.get_source_count()).map(|id| sm.get_source_contents(id).map(owned)).collect(),
+            tokens,
+            None,
+        );
+        let ignore_list: Vec<u32> = sm.ignore_list().copied().collect();
+        if !ignore_list.is_empty() {
+            map.x_google_ignore_list = Some(ignore_list);
+        }
+        map.debug_id = sm.get_debug_id().map(|id| Cow::Owned(id.to_string()));
+        map
+    }
+
+    /// Copy this map into a new [`sourcemap::SourceMap`].
+    ///
+    /// A `debugId` that is not a valid debug identifier is dropped.
+    pub fn to_sourcemap_crate(&self) -> sourcemap::SourceMap {
+        let arc = |s: &Cow<'_, str>| Arc::<str>::from(&**s);
+        let tokens = self
+            .tokens
+            .iter()
+            .map(|token| sourcemap::RawToken {
+                dst_line: token.dst_line,
+                dst_col: token.dst_col,
+                src_line: token.src_line,
+                src_col: token.src_col,
+                src_id: token.get_source_id().unwrap_or(INVALID_ID),
+                name_id: token.get_name_id().unwrap_or(INVALID_ID),
+                is_range: false,
+            })
+            .collect();
+        let sources_content = self.source_contents.iter().any(Option::is_some).then(|| {
+            self.source_contents.iter().map(|content| content.as_ref().map(arc)).collect()
+        });
+        let mut sm = sourcemap::SourceMap::new(
+            self.file.as_ref().map(arc),
+            tokens,
+            self.names.iter().map(arc).collect(),
+            self.sources.iter().map(arc).collect(),
+            sources_content,
+        );
+        sm.set_source_root(self.source_root.as_ref().map(arc));
+        for &source_id in self.x_google_ignore_list.iter().flatten() {
+            sm.add_to_ignore_list(source_id);
+        }
+        sm.set_debug_id(self.get_debug_id().and_then(|id| id.parse().ok()));
+        sm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r#"{
+        "version": 3,
+        "file": "out.js",
+        "names": ["x"],
+        "sources": ["a.js", "b.js"],
+        "sourcesContent": ["let x;", null],
+        "x_google_ignoreList": [1],
+        "debugId": "56431d54-c0a6-451d-8ea2-ba5de5d8ca2e",
+        "mappings": "AAAAA,CCAA;A"
+    }"#;
+
+    #[test]
+    fn roundtrip() {
+        let sm = SourceMap::from_json_string(JSON).unwrap();
+        let converted = sm.to_sourcemap_crate();
+        assert_eq!(converted.get_token_count(), 3);
+        assert_eq!(converted.get_source_contents(0), Some("let x;"));
+
+        let back = SourceMap::from_sourcemap_crate(&converted);
+        assert_eq!(back.get_tokens().collect::<Vec<_>>(), sm.get_tokens().collect::<Vec<_>>());
+        assert_eq!(back.to_json_string(), sm.to_json_string());
+    }
+
+    #[test]
+    fn source_root_is_resolved() {
+        let json =
+            r#"{"version":3,"sourceRoot":"src/","names":[],"sources":["a.js"],"mappings":"AAAA"}"#;
+        let converted = SourceMap::from_json_string(json).unwrap().to_sourcemap_crate();
+        assert_eq!(converted.get_source_root(), Some("src/"));
+        let back = SourceMap::from_sourcemap_crate(&converted);
+        assert_eq!(back.get_source_root(), None);
+        assert_eq!(back.get_sources().collect::<Vec<_>>(), vec!["src/a.js"]);
+    }
+}
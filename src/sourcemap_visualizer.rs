@@ -1,6 +1,6 @@
 use std::{borrow::Cow, fmt::Write};
 
-use crate::SourceMap;
+use crate::{SourceMap, Token};
 
 /// The `SourcemapVisualizer` is a helper for sourcemap testing.
 /// It print the mapping of original content and final content tokens.
@@ -22,48 +22,205 @@ impl<'a, 'sm> SourcemapVisualizer<'a, 'sm> {
     }
 
     pub fn get_text(&self) -> String {
+        self.render_text(false)
+    }
+
+    /// Same as [`Self::get_text`], with ANSI colors for terminals: each source file gets its own
+    /// color for its header and snippets, and invalid positions are dimmed.
+    ///
+    /// Plain text is returned when `no_color` is set or the `NO_COLOR` environment variable is
+    /// set to a non-empty value (<https://no-color.org>), so piped output stays readable.
+    /// Stripping the escape sequences from the colored output yields exactly [`Self::get_text`].
+    pub fn get_text_colored(&self, no_color: bool) -> String {
+        let no_color = no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        self.render_text(!no_color)
+    }
+
+    fn render_text(&self, colored: bool) -> String {
         let mut s = String::new();
-        let source_contents = &self.sourcemap.source_contents;
         if self.sourcemap.source_contents.is_empty() {
             s.push_str("[no source contents]\n");
             return s;
         }
 
+        let tables = self.line_tables();
+        let mut last_source: Option<&str> = None;
+        for mapping in self.mappings(&tables) {
+            // Print source
+            if last_source != Some(mapping.source) {
+                s.push('-');
+                s.push(' ');
+                paint(&mut s, colored.then(|| mapping.color()), |s| s.push_str(mapping.source));
+                s.push('\n');
+                last_source = Some(mapping.source);
+            }
+
+            mapping.write(&mut s, &tables, colored);
+            s.push('\n');
+        }
+
+        s
+    }
+
+    /// Compare two sourcemaps over the same generated `code`, aligning tokens by destination
+    /// position.
+    ///
+    /// Each reported token is printed in the [`get_text`](Self::get_text) style, prefixed by its
+    /// source file and a marker:
+    /// * `-` — the destination position is only mapped by `a`.
+    /// * `+` — the destination position is only mapped by `b`.
+    /// * `~` — both map it, to a different source position; printed as `b`'s mapping followed by
+    ///   `(was ..)` with `a`'s source side.
+    ///
+    /// Tokens that `get_text` skips (no source, or no content for their source) are not compared.
+    /// Returns an empty string when the maps agree.
+    pub fn diff(code: &str, a: &SourceMap<'_>, b: &SourceMap<'_>) -> String {
+        let (a, b) = (SourcemapVisualizer::new(code, a), SourcemapVisualizer::new(code, b));
+        let (a_tables, b_tables) = (a.line_tables(), b.line_tables());
+        let mut a_mappings: Vec<_> = a.mappings(&a_tables).collect();
+        let mut b_mappings: Vec<_> = b.mappings(&b_tables).collect();
+        // Stable sorts, so tokens sharing a destination position pair up in map order.
+        a_mappings.sort_by_key(Mapping::dst_position);
+        b_mappings.sort_by_key(Mapping::dst_position);
+
+        let mut s = String::new();
+        let mut a_iter = a_mappings.iter().peekable();
+        let mut b_iter = b_mappings.iter().peekable();
+        loop {
+            match (a_iter.peek(), b_iter.peek()) {
+                (None, None) => break,
+                (Some(x), Some(y)) if x.dst_position() == y.dst_position() => {
+                    if x.src_position() != y.src_position() {
+                        s.push_str("~ ");
+                        y.write_with_source(&mut s, &b_tables);
+                        s.push_str(" (was ");
+                        s.push_str(x.source);
+                        s.push(' ');
+                        x.write_src(&mut s, &a_tables, false);
+                        s.push_str(")\n");
+                    }
+                    a_iter.next();
+                    b_iter.next();
+                }
+                (Some(x), y) if y.is_none_or(|y| x.dst_position() < y.dst_position()) => {
+                    s.push_str("- ");
+                    x.write_with_source(&mut s, &a_tables);
+                    s.push('\n');
+                    a_iter.next();
+                }
+                (_, Some(y)) => {
+                    s.push_str("+ ");
+                    y.write_with_source(&mut s, &b_tables);
+                    s.push('\n');
+                    b_iter.next();
+                }
+                (Some(_), None) => unreachable!(),
+            }
+        }
+        s
+    }
+
+    /// Render a self-contained HTML page with the original sources and the generated code side by
+    /// side. Hovering a mapped region highlights it together with its counterpart in the other
+    /// pane.
+    ///
+    /// The mapped ranges are computed the same way as in [`get_text`](Self::get_text) and embedded
+    /// as a JSON array of `[[source_id, line, start_col, end_col], [line, start_col, end_col]]`
+    /// tuples (columns in UTF-16 code units), which a small inline script turns into highlights.
+    ///
+    /// Consumes the visualizer, which only borrows the code and the map.
+    pub fn into_html(self) -> String {
+        let tables = self.line_tables();
+        let ranges: Vec<_> = self
+            .mappings(&tables)
+            .filter(|mapping| !mapping.is_invalid())
+            .map(|mapping| {
+                let t = mapping.token;
+                let (src_start, src_end) = ordered(t.src_col, mapping.src_end_col);
+                let (dst_start, dst_end) = ordered(t.dst_col, mapping.dst_end_col);
+                (
+                    (mapping.source_id, t.src_line, src_start, src_end),
+                    (t.dst_line, dst_start, dst_end),
+                )
+            })
+            .collect();
+        let sources: Vec<_> = self
+            .sourcemap
+            .get_sources()
+            .enumerate()
+            .map(|(id, name)| (name, self.sourcemap.get_source_content(id as u32)))
+            .collect();
+        let data = serde_json::json!({
+            "code": self.code,
+            "sources": sources,
+            "mappings": ranges,
+        });
+        // Escape `<` so the embedded code can't close the `<script>` element early. `<` can only
+        // occur inside JSON strings, where `\u003c` is an equivalent escape.
+        let data = data.to_string().replace('<', "\\u003c");
+
+        HTML_TEMPLATE.replacen("{{DATA}}", &data, 1)
+    }
+
+    /// Dump the mappings as a JSON array (pretty-printed, one token per object) for automated
+    /// comparisons, e.g. diffing two builds with `jq`.
+    ///
+    /// Each object has the shape
+    /// `{"src": {"file", "line", "col", "text"}, "dst": {"line", "col", "text"}, "name"}`, where
+    /// `text` is the snippet [`get_text`](Self::get_text) would print for that side, or `null`
+    /// when the position is invalid. Tokens are skipped under the same rules as `get_text`.
+    pub fn to_mappings_json(&self) -> String {
+        let tables = self.line_tables();
+        let mappings: Vec<_> = self
+            .mappings(&tables)
+            .map(|mapping| {
+                let t = &mapping.token;
+                let src_text = (!mapping.is_invalid()).then(|| mapping.src_snippet(&tables));
+                let dst_text = (!mapping.is_invalid()).then(|| mapping.dst_snippet(&tables));
+                serde_json::json!({
+                    "src": {
+                        "file": mapping.source,
+                        "line": t.src_line,
+                        "col": t.src_col,
+                        "text": src_text,
+                    },
+                    "dst": {
+                        "line": t.dst_line,
+                        "col": t.dst_col,
+                        "text": dst_text,
+                    },
+                    "name": t.get_name_id().and_then(|id| self.sourcemap.get_name(id)),
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&mappings).unwrap()
+    }
+
+    /// Build the UTF-16 line tables for the generated code and every source content.
+    fn line_tables(&self) -> LineTables {
         // Build a 1:1 map: index N in the result corresponds to source_id N.
         // `None` entries are preserved so indexing by source_id stays correct
         // even when some sources have no content (the previous filter_map
         // dropped them, which misaligned all later indices).
-        let source_contents_lines_map: Vec<Option<Vec<Vec<u16>>>> = source_contents
+        let sources = self
+            .sourcemap
+            .source_contents
             .iter()
             .map(|content| content.as_ref().map(|c| Self::generate_line_utf16_tables(c)))
             .collect();
+        LineTables { output: Self::generate_line_utf16_tables(self.code), sources }
+    }
 
-        let output_lines = Self::generate_line_utf16_tables(self.code);
-
+    /// Resolve every token against `tables`, skipping tokens without a source or without content
+    /// for their source.
+    fn mappings<'t>(&'t self, tables: &'t LineTables) -> impl Iterator<Item = Mapping<'t>> {
         let tokens = &self.sourcemap.tokens;
-
-        let mut last_source: Option<&str> = None;
-        for i in 0..tokens.len() {
-            let t = &tokens[i];
-            let Some(source_id) = t.get_source_id() else {
-                continue;
-            };
-            let Some(source) = self.sourcemap.get_source(source_id) else { continue };
-            let Some(source_lines) =
-                source_contents_lines_map.get(source_id as usize).and_then(|opt| opt.as_ref())
-            else {
-                // No content for this source; skip rather than panic.
-                continue;
-            };
-
-            // Print source
-            if last_source != Some(source) {
-                s.push('-');
-                s.push(' ');
-                s.push_str(source);
-                s.push('\n');
-                last_source = Some(source);
-            }
+        let output_lines = &tables.output;
+        tokens.iter().enumerate().filter_map(move |(i, t)| {
+            let source_id = t.get_source_id()?;
+            let source = self.sourcemap.get_source(source_id)?;
+            // No content for this source; skip rather than panic.
+            let source_lines = tables.sources.get(source_id as usize)?.as_ref()?;
 
             // validate token position
             let dst_invalid = t.dst_line as usize >= output_lines.len()
@@ -71,18 +228,15 @@ impl<'a, 'sm> SourcemapVisualizer<'a, 'sm> {
             let src_invalid = t.src_line as usize >= source_lines.len()
                 || (t.src_col as usize) >= source_lines[t.src_line as usize].len();
             if dst_invalid || src_invalid {
-                writeln!(
-                    s,
-                    "({}:{}){} --> ({}:{}){}",
-                    t.src_line,
-                    t.src_col,
-                    if src_invalid { " [invalid]" } else { "" },
-                    t.dst_line,
-                    t.dst_col,
-                    if dst_invalid { " [invalid]" } else { "" },
-                )
-                .unwrap();
-                continue;
+                return Some(Mapping {
+                    source,
+                    source_id,
+                    token: *t,
+                    src_invalid,
+                    dst_invalid,
+                    src_end_col: t.src_col,
+                    dst_end_col: t.dst_col,
+                });
             }
 
             // find next dst column or EOL
@@ -108,43 +262,26 @@ impl<'a, 'sm> SourcemapVisualizer<'a, 'sm> {
                 source_lines[t.src_line as usize].len() as u32
             };
 
-            writeln!(
-                s,
-                "({}:{}) {:?} --> ({}:{}) {:?}",
-                t.src_line,
-                t.src_col,
-                Self::str_slice_by_token(source_lines, t.src_line, t.src_col, src_end_col),
-                t.dst_line,
-                t.dst_col,
-                Self::str_slice_by_token(&output_lines, t.dst_line, t.dst_col, dst_end_col)
-            )
-            .unwrap();
-        }
-
-        s
+            Some(Mapping {
+                source,
+                source_id,
+                token: *t,
+                src_invalid,
+                dst_invalid,
+                src_end_col,
+                dst_end_col,
+            })
+        })
     }
 
     fn generate_line_utf16_tables(content: &str) -> Vec<Vec<u16>> {
-        let mut tables = vec![];
-        let mut line_byte_offset = 0;
-        let bytes = content.as_bytes();
-        for (i, ch) in content.char_indices() {
-            match ch {
-                '\r' | '\n' | '\u{2028}' | '\u{2029}' => {
-                    // Handle Windows-specific "\r\n" newlines. `\n` is a single
-                    // ASCII byte, so peeking the next byte is correct even when
-                    // earlier content contains multi-byte UTF-8.
-                    if ch == '\r' && bytes.get(i + 1) == Some(&b'\n') {
-                        continue;
-                    }
-                    tables.push(content[line_byte_offset..=i].encode_utf16().collect::<Vec<_>>());
-                    line_byte_offset = i + 1;
-                }
-                _ => {}
-            }
-        }
-        tables.push(content[line_byte_offset..].encode_utf16().collect::<Vec<_>>());
-        tables
+        let starts = crate::sourcemap::line_starts(content);
+        let ends = starts.iter().skip(1).copied().chain([content.len() as u32]);
+        starts
+            .iter()
+            .zip(ends)
+            .map(|(&start, end)| content[start as usize..end as usize].encode_utf16().collect())
+            .collect()
     }
 
     fn str_slice_by_token(buff: &[Vec<u16>], line: u32, start: u32, end: u32) -> Cow<'_, str> {
@@ -159,12 +296,224 @@ impl<'a, 'sm> SourcemapVisualizer<'a, 'sm> {
     }
 }
 
+/// UTF-16 line tables for the generated code and for every source content (indexed by source id).
+struct LineTables {
+    output: Vec<Vec<u16>>,
+    sources: Vec<Option<Vec<Vec<u16>>>>,
+}
+
+/// A token resolved against [`LineTables`], with the columns where its mapped region ends on the
+/// source and generated side. The end columns equal the start columns for invalid positions.
+struct Mapping<'a> {
+    source: &'a str,
+    source_id: u32,
+    token: Token,
+    src_invalid: bool,
+    dst_invalid: bool,
+    src_end_col: u32,
+    dst_end_col: u32,
+}
+
+impl Mapping<'_> {
+    fn dst_position(&self) -> (u32, u32) {
+        (self.token.dst_line, self.token.dst_col)
+    }
+
+    fn src_position(&self) -> (&str, u32, u32) {
+        (self.source, self.token.src_line, self.token.src_col)
+    }
+
+    fn is_invalid(&self) -> bool {
+        self.src_invalid || self.dst_invalid
+    }
+
+    /// Write `(src_line:src_col) "snippet" --> (dst_line:dst_col) "snippet"`. When either side is
+    /// out of bounds, the snippets are omitted and the offending side is marked `[invalid]`.
+    ///
+    /// With `colored`, snippets are wrapped in the source's ANSI color and invalid sides are
+    /// dimmed; the text between the escapes is unchanged.
+    fn write(&self, s: &mut String, tables: &LineTables, colored: bool) {
+        self.write_src(s, tables, colored);
+        s.push_str(" --> ");
+        self.write_dst(s, tables, colored);
+    }
+
+    /// Same as [`Self::write`], prefixed by the source file.
+    fn write_with_source(&self, s: &mut String, tables: &LineTables) {
+        s.push_str(self.source);
+        s.push(' ');
+        self.write(s, tables, false);
+    }
+
+    fn write_src(&self, s: &mut String, tables: &LineTables, colored: bool) {
+        let t = &self.token;
+        if self.is_invalid() {
+            let dim = colored && self.src_invalid;
+            paint(s, dim.then_some(ansi::DIM), |s| {
+                let marker = if self.src_invalid { " [invalid]" } else { "" };
+                write!(s, "({}:{}){marker}", t.src_line, t.src_col).unwrap();
+            });
+        } else {
+            write!(s, "({}:{}) ", t.src_line, t.src_col).unwrap();
+            paint(s, colored.then(|| self.color()), |s| {
+                write!(s, "{:?}", self.src_snippet(tables)).unwrap();
+            });
+        }
+    }
+
+    fn write_dst(&self, s: &mut String, tables: &LineTables, colored: bool) {
+        let t = &self.token;
+        if self.is_invalid() {
+            let dim = colored && self.dst_invalid;
+            paint(s, dim.then_some(ansi::DIM), |s| {
+                let marker = if self.dst_invalid { " [invalid]" } else { "" };
+                write!(s, "({}:{}){marker}", t.dst_line, t.dst_col).unwrap();
+            });
+        } else {
+            write!(s, "({}:{}) ", t.dst_line, t.dst_col).unwrap();
+            paint(s, colored.then(|| self.color()), |s| {
+                write!(s, "{:?}", self.dst_snippet(tables)).unwrap();
+            });
+        }
+    }
+
+    fn color(&self) -> &'static str {
+        ansi::PALETTE[self.source_id as usize % ansi::PALETTE.len()]
+    }
+
+    fn src_snippet<'t>(&self, tables: &'t LineTables) -> Cow<'t, str> {
+        let lines = tables.sources[self.source_id as usize].as_ref().unwrap();
+        let t = &self.token;
+        SourcemapVisualizer::str_slice_by_token(lines, t.src_line, t.src_col, self.src_end_col)
+    }
+
+    fn dst_snippet<'t>(&self, tables: &'t LineTables) -> Cow<'t, str> {
+        let t = &self.token;
+        SourcemapVisualizer::str_slice_by_token(
+            &tables.output,
+            t.dst_line,
+            t.dst_col,
+            self.dst_end_col,
+        )
+    }
+}
+
+/// Run `f`, wrapping whatever it writes in the `style` escape sequence (if any).
+fn paint<F: FnOnce(&mut String)>(s: &mut String, style: Option<&str>, f: F) {
+    if let Some(style) = style {
+        s.push_str(style);
+        f(s);
+        s.push_str(ansi::RESET);
+    } else {
+        f(s);
+    }
+}
+
+mod ansi {
+    pub const RESET: &str = "\x1b[0m";
+    pub const DIM: &str = "\x1b[2m";
+    /// Foreground colors cycled through per source file.
+    pub const PALETTE: [&str; 6] =
+        ["\x1b[32m", "\x1b[33m", "\x1b[34m", "\x1b[35m", "\x1b[36m", "\x1b[31m"];
+}
+
+fn ordered(a: u32, b: u32) -> (u32, u32) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+const HTML_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Source map visualization</title>
+<style>
+body { margin: 0; display: flex; height: 100vh; font: 13px/1.5 ui-monospace, monospace; }
+.pane { flex: 1; overflow: auto; padding: 8px; white-space: pre; }
+.pane + .pane { border-left: 1px solid #ccc; }
+.pane h2 { font-size: 13px; margin: 8px 0; color: #555; }
+.line { min-height: 1.5em; }
+.m { background: rgba(0, 120, 255, 0.08); cursor: pointer; }
+.m.hl { background: rgba(255, 190, 0, 0.6); }
+</style>
+</head>
+<body>
+<div class="pane" id="original"></div>
+<div class="pane" id="generated"></div>
+<script type="application/json" id="data">{{DATA}}</script>
+<script>
+const data = JSON.parse(document.getElementById("data").textContent);
+const spans = [];
+function render(pane, text, ranges) {
+  const byLine = new Map();
+  for (const range of ranges) {
+    if (!byLine.has(range[0])) byLine.set(range[0], []);
+    byLine.get(range[0]).push(range);
+  }
+  text.split(/\r\n|[\n\r\u2028\u2029]/).forEach((line, n) => {
+    const row = document.createElement("div");
+    row.className = "line";
+    const lineRanges = byLine.get(n) || [];
+    const cuts = new Set([0, line.length]);
+    for (const [, start, end] of lineRanges) {
+      cuts.add(Math.min(start, line.length));
+      cuts.add(Math.min(end, line.length));
+    }
+    const points = [...cuts].sort((a, b) => a - b);
+    for (let k = 0; k + 1 < points.length; k++) {
+      const span = document.createElement("span");
+      span.textContent = line.slice(points[k], points[k + 1]);
+      const ids = lineRanges
+        .filter(([, start, end]) => start <= points[k] && points[k + 1] <= end)
+        .map((range) => range[3]);
+      if (ids.length) {
+        span.className = "m";
+        span.dataset.m = ids.join(" ");
+        for (const id of ids) (spans[id] ||= []).push(span);
+      }
+      row.append(span);
+    }
+    pane.append(row);
+  });
+}
+const original = document.getElementById("original");
+data.sources.forEach(([name, content], id) => {
+  const title = document.createElement("h2");
+  title.textContent = name;
+  original.append(title);
+  const ranges = [];
+  data.mappings.forEach(([[sourceId, line, start, end]], i) => {
+    if (sourceId === id) ranges.push([line, start, end, i]);
+  });
+  if (content !== null) render(original, content, ranges);
+});
+render(
+  document.getElementById("generated"),
+  data.code,
+  data.mappings.map(([, [line, start, end]], i) => [line, start, end, i]),
+);
+let active = [];
+document.addEventListener("mouseover", (event) => {
+  for (const span of active) span.classList.remove("hl");
+  active = [];
+  const ids = event.target.dataset && event.target.dataset.m;
+  if (!ids) return;
+  for (const id of ids.split(" ")) {
+    for (const span of spans[id]) {
+      span.classList.add("hl");
+      active.push(span);
+    }
+  }
+});
+</script>
+</body>
+</html>
+"##;
+
 #[cfg(test)]
 mod tests {
     use std::borrow::Cow;
 
     use super::*;
-    use crate::Token;
 
     #[test]
     fn get_url() {
@@ -225,6 +574,18 @@ mod tests {
         assert!(text.contains("- a.js"), "{text}");
     }
 
+    #[test]
+    fn line_tables_with_mixed_line_endings() {
+        // `\r\n`, lone `\r`, `\n` and U+2028/U+2029 each end a line, and the terminator stays
+        // at the end of its line. The CRLF check peeks one byte ahead, so it stays linear and
+        // is unaffected by multi-byte characters before it.
+        let tables =
+            SourcemapVisualizer::generate_line_utf16_tables("a\r\n👀\nb\rc\u{2028}d\u{2029}\r\ne");
+        let lines: Vec<String> =
+            tables.iter().map(|line| String::from_utf16(line).unwrap()).collect();
+        assert_eq!(lines, vec!["a\r\n", "👀\n", "b\r", "c\u{2028}", "d\u{2029}", "\r\n", "e"]);
+    }
+
     #[test]
     fn skips_token_with_out_of_range_source() {
         // A token references a source id past the end of `sources`; the
@@ -240,4 +601,92 @@ mod tests {
         );
         assert_eq!(SourcemapVisualizer::new("aa\n", &sm).get_text(), "");
     }
+
+    #[test]
+    fn html_embeds_panes_and_ranges() {
+        let sm = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![Cow::Borrowed("a.js")],
+            vec![Some(Cow::Borrowed("let a = 1;</script>\n"))],
+            vec![Token::new(0, 0, 0, 0, Some(0), None), Token::new(0, 4, 0, 4, Some(0), None)]
+                .into_boxed_slice(),
+            None,
+        );
+        let html = SourcemapVisualizer::new("let a=1;\n", &sm).into_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        // Source content is escaped so it can't terminate the data `<script>` element.
+        assert!(!html.contains("1;</script>"), "{html}");
+        assert!(html.contains(r"let a = 1;\u003c/script>"), "{html}");
+        // `[[source_id, line, start, end], [line, start, end]]` per valid token.
+        assert!(html.contains("[[[0,0,0,4],[0,0,4]],[[0,0,4,20],[0,4,9]]]"), "{html}");
+    }
+
+    #[test]
+    fn diff() {
+        let map = |tokens: Vec<Token>| {
+            SourceMap::new(
+                None,
+                vec![],
+                None,
+                vec![Cow::Borrowed("a.js")],
+                vec![Some(Cow::Borrowed("let a = 1;\nlet b = 2;\n"))],
+                tokens.into_boxed_slice(),
+                None,
+            )
+        };
+        let code = "let a=1;let b=2;\n";
+        let a = map(vec![
+            Token::new(0, 0, 0, 0, Some(0), None),
+            Token::new(0, 4, 0, 4, Some(0), None),
+            Token::new(0, 8, 1, 0, Some(0), None),
+        ]);
+        let b = map(vec![
+            Token::new(0, 0, 0, 0, Some(0), None),
+            Token::new(0, 8, 1, 4, Some(0), None),
+            Token::new(0, 12, 1, 4, Some(0), None),
+        ]);
+        assert_eq!(SourcemapVisualizer::diff(code, &a, &a), "");
+        assert_eq!(
+            SourcemapVisualizer::diff(code, &a, &b),
+            "- a.js (0:4) \"a = 1;\\n\" --> (0:4) \"a=1;\"\n\
+             ~ a.js (1:4) \"b = 2;\\n\" --> (0:8) \"let \" (was a.js (1:0) \"let b = 2;\\n\")\n\
+             + a.js (1:4) \"b = 2;\\n\" --> (0:12) \"b=2;\\n\"\n"
+        );
+    }
+
+    #[test]
+    fn colored_text_strips_to_plain() {
+        let sm = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![Cow::Borrowed("a.js"), Cow::Borrowed("b.js")],
+            vec![Some(Cow::Borrowed("let a = 1;\n")), Some(Cow::Borrowed("b\n"))],
+            vec![
+                Token::new(0, 0, 0, 0, Some(0), None),
+                Token::new(0, 4, 0, 4, Some(1), None),
+                Token::new(0, 6, 9, 0, Some(1), None),
+            ]
+            .into_boxed_slice(),
+            None,
+        );
+        let visualizer = SourcemapVisualizer::new("let a=1;\n", &sm);
+        let plain = visualizer.get_text();
+        assert_eq!(visualizer.get_text_colored(true), plain);
+
+        let colored = visualizer.render_text(true);
+        assert!(colored.contains("\x1b[32m\"let \"\x1b[0m"), "{colored}");
+        assert!(colored.contains("- \x1b[33mb.js\x1b[0m"), "{colored}");
+        assert!(colored.contains("\x1b[2m(9:0) [invalid]\x1b[0m"), "{colored}");
+        let mut stripped = String::new();
+        let mut rest = colored.as_str();
+        while let Some(start) = rest.find('\x1b') {
+            stripped.push_str(&rest[..start]);
+            rest = &rest[start + rest[start..].find('m').unwrap() + 1..];
+        }
+        stripped.push_str(rest);
+        assert_eq!(stripped, plain);
+    }
 }
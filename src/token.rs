@@ -1,3 +1,7 @@
+use std::{fmt, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
 use crate::SourceMap;
 
 /// Sentinel value representing an invalid/missing ID for source or name.
@@ -5,8 +9,17 @@ use crate::SourceMap;
 pub(crate) const INVALID_ID: u32 = u32::MAX;
 
 /// The `Token` is used to generate vlq `mappings`.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+///
+/// Tokens order destination-first: by `(dst_line, dst_col)`, then by the original position and
+/// the source/name ids (a missing id sorts after every present one). Sorting a token vector
+/// therefore yields the generated-position order that lookups and encoding rely on.
+///
+/// Serializes as an object of its six fields, with a missing `source_id` / `name_id` as `null`.
+/// Debug-formats compactly as `dst=(L:C) src=(L:C) source=<id|none> name=<id|none>`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[serde(from = "TokenRepr", into = "TokenRepr")]
 pub struct Token {
+    // Field order defines the derived ordering; keep the destination position first.
     pub(crate) dst_line: u32,
     pub(crate) dst_col: u32,
     pub(crate) src_line: u32,
@@ -34,6 +47,20 @@ impl Token {
         }
     }
 
+    /// Like [`Token::new`], but taking the raw ids as stored: `u32::MAX` marks a missing source or
+    /// name. Skips the `Option` round-trip for callers that already hold ids in that form.
+    #[inline]
+    pub fn from_raw(
+        dst_line: u32,
+        dst_col: u32,
+        src_line: u32,
+        src_col: u32,
+        source_id: u32,
+        name_id: u32,
+    ) -> Self {
+        Self { dst_line, dst_col, src_line, src_col, source_id, name_id }
+    }
+
     /// Translate this token for a concatenated source map: shift the generated line by
     /// `line_offset` and renumber the source/name ids by `source_offset` / `name_offset`,
     /// preserving the missing-id sentinel. Operates on the raw ids so there is no `Option`
@@ -51,6 +78,36 @@ impl Token {
         }
     }
 
+    /// Pair this token with the map it came from, to resolve its source and name.
+    ///
+    /// ```
+    /// use oxc_sourcemap::SourceMap;
+    ///
+    /// let json = r#"{"version":3,"sources":["a.js"],"names":["x"],"mappings":"AAAAA"}"#;
+    /// let sm = SourceMap::from_json_string(json).unwrap();
+    /// for token in sm.get_tokens() {
+    ///     let view = token.resolve(&sm);
+    ///     assert_eq!(view.get_source(), Some("a.js"));
+    ///     assert_eq!(view.get_name(), Some("x"));
+    /// }
+    /// ```
+    #[inline]
+    pub fn resolve<'sm, 'data>(&self, sm: &'sm SourceMap<'data>) -> SourceViewToken<'sm, 'data> {
+        SourceViewToken::new(*self, sm)
+    }
+
+    /// Replace the raw source/name ids, e.g. after renumbering a map's `sources` or `names`.
+    #[inline]
+    pub(crate) fn set_raw_ids(&mut self, source_id: u32, name_id: u32) {
+        self.source_id = source_id;
+        self.name_id = name_id;
+    }
+
+    #[inline]
+    pub(crate) fn raw_ids(&self) -> (u32, u32) {
+        (self.source_id, self.name_id)
+    }
+
     #[inline]
     pub fn get_dst_line(&self) -> u32 {
         self.dst_line
@@ -82,7 +139,70 @@ impl Token {
     }
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = |id: u32| if id == INVALID_ID { "none".to_string() } else { id.to_string() };
+        write!(
+            f,
+            "dst=({}:{}) src=({}:{}) source={} name={}",
+            self.dst_line,
+            self.dst_col,
+            self.src_line,
+            self.src_col,
+            id(self.source_id),
+            id(self.name_id)
+        )
+    }
+}
+
+/// Serialized form of [`Token`]: the same fields, with missing ids as `null` instead of the
+/// internal sentinel.
+#[derive(Serialize, Deserialize)]
+struct TokenRepr {
+    dst_line: u32,
+    dst_col: u32,
+    src_line: u32,
+    src_col: u32,
+    source_id: Option<u32>,
+    name_id: Option<u32>,
+}
+
+impl From<Token> for TokenRepr {
+    fn from(token: Token) -> Self {
+        Self {
+            dst_line: token.dst_line,
+            dst_col: token.dst_col,
+            src_line: token.src_line,
+            src_col: token.src_col,
+            source_id: token.get_source_id(),
+            name_id: token.get_name_id(),
+        }
+    }
+}
+
+impl From<TokenRepr> for Token {
+    fn from(repr: TokenRepr) -> Self {
+        Token::new(
+            repr.dst_line,
+            repr.dst_col,
+            repr.src_line,
+            repr.src_col,
+            repr.source_id,
+            repr.name_id,
+        )
+    }
+}
+
+/// Where a [`Token`]'s generated and original ranges end, for tools that record mapping ranges
+/// rather than only start positions. Stored on the [`SourceMap`] in parallel with its tokens, see
+/// [`SourceMap::set_token_ends`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenEnd {
+    pub dst_end_col: Option<u32>,
+    pub src_end_col: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TokenChunk {
     pub start: u32,
     pub end: u32,
@@ -176,10 +296,78 @@ impl<'sm, 'data> SourceViewToken<'sm, 'data> {
         self.get_source_id().and_then(|id| self.sourcemap.get_source_and_content(id))
     }
 
+    /// The generated `(line, column)` this token starts at.
+    pub fn dst_position(&self) -> (u32, u32) {
+        (self.token.dst_line, self.token.dst_col)
+    }
+
+    /// The next token on the same generated line, if any. The generated range this token covers
+    /// runs up to that token's column, or to the end of the line when there is none.
+    pub fn next_in_line(&self) -> Option<SourceViewToken<'sm, 'data>> {
+        let tokens = &self.sourcemap.tokens;
+        let position = self.dst_position();
+        let next = tokens.partition_point(|token| (token.dst_line, token.dst_col) <= position);
+        let token = *tokens.get(next)?;
+        (token.dst_line == position.0).then(|| SourceViewToken::new(token, self.sourcemap))
+    }
+
+    /// Whether this token maps into a source marked third-party by `x_google_ignoreList`.
+    pub fn is_ignored(&self) -> bool {
+        self.get_source_id().is_some_and(|id| self.sourcemap.is_source_ignored(id))
+    }
+
     #[expect(clippy::wrong_self_convention)]
     pub fn to_tuple(&self) -> (Option<&'sm str>, u32, u32, Option<&'sm str>) {
         (self.get_source(), self.get_src_line(), self.get_src_col(), self.get_name())
     }
+
+    /// Copy the token with its source path and name, so it can outlive the [`SourceMap`] borrow.
+    pub fn into_owned(self) -> OwnedSourceViewToken {
+        OwnedSourceViewToken {
+            token: self.token,
+            source: self.get_source().map(Into::into),
+            name: self.get_name().map(Into::into),
+        }
+    }
+}
+
+/// A [`SourceViewToken`] holding its own copies of the source path and name, see
+/// [`SourceViewToken::into_owned`]. The source content is not copied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedSourceViewToken {
+    token: Token,
+    source: Option<Arc<str>>,
+    name: Option<Arc<str>>,
+}
+
+impl OwnedSourceViewToken {
+    pub fn token(&self) -> Token {
+        self.token
+    }
+
+    pub fn get_dst_line(&self) -> u32 {
+        self.token.dst_line
+    }
+
+    pub fn get_dst_col(&self) -> u32 {
+        self.token.dst_col
+    }
+
+    pub fn get_src_line(&self) -> u32 {
+        self.token.src_line
+    }
+
+    pub fn get_src_col(&self) -> u32 {
+        self.token.src_col
+    }
+
+    pub fn get_source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
 }
 
 #[cfg(test)]
@@ -262,4 +450,81 @@ mod tests {
         assert_eq!(token.get_source_content(), None);
         assert_eq!(token.get_source_and_content(), None);
     }
+
+    #[test]
+    fn token_ordering() {
+        let mut tokens = vec![
+            Token::new(1, 0, 0, 0, Some(0), None),
+            Token::new(0, 5, 3, 0, Some(0), None),
+            Token::new(0, 5, 1, 0, Some(0), None),
+            Token::new(2, 1, 0, 0, None, None),
+            Token::new(0, 0, 9, 9, Some(0), None),
+        ];
+        tokens.sort();
+        let positions: Vec<_> =
+            tokens.iter().map(|t| (t.get_dst_line(), t.get_dst_col(), t.get_src_line())).collect();
+        assert_eq!(positions, vec![(0, 0, 9), (0, 5, 1), (0, 5, 3), (1, 0, 0), (2, 1, 0)]);
+        assert!(Token::new(0, 0, 0, 0, Some(1), None) < Token::new(0, 0, 0, 0, None, None));
+
+        // Sorted tokens are lookup-ready.
+        let sm = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![Cow::Borrowed("a.js")],
+            vec![],
+            tokens.into_boxed_slice(),
+            None,
+        );
+        let table = sm.generate_lookup_table();
+        assert_eq!(sm.lookup_token(&table, 0, 5).unwrap().get_src_line(), 1);
+        assert_eq!(sm.lookup_token(&table, 1, 3).unwrap().get_dst_line(), 1);
+        assert_eq!(sm.lookup_token(&table, 2, 1).unwrap().get_source_id(), None);
+    }
+
+    #[test]
+    fn token_serde_roundtrip() {
+        let token = Token::new(1, 2, 3, 4, Some(5), None);
+        let json = serde_json::to_string(&token).unwrap();
+        assert_eq!(
+            json,
+            r#"{"dst_line":1,"dst_col":2,"src_line":3,"src_col":4,"source_id":5,"name_id":null}"#
+        );
+        assert_eq!(serde_json::from_str::<Token>(&json).unwrap(), token);
+
+        let chunk = TokenChunk::new(0, 2, 1, 2, 3, 4, 5, 6);
+        let json = serde_json::to_string(&chunk).unwrap();
+        assert_eq!(serde_json::from_str::<TokenChunk>(&json).unwrap(), chunk);
+    }
+
+    #[test]
+    fn from_raw() {
+        let token = Token::from_raw(1, 2, 3, 4, INVALID_ID, INVALID_ID);
+        assert_eq!(token.get_source_id(), None);
+        assert_eq!(token.get_name_id(), None);
+        assert_eq!(token, Token::new(1, 2, 3, 4, None, None));
+        assert_eq!(Token::from_raw(1, 2, 3, 4, 0, 5), Token::new(1, 2, 3, 4, Some(0), Some(5)));
+    }
+
+    #[test]
+    fn debug_format() {
+        let token = Token::new(1, 2, 3, 4, None, Some(5));
+        assert_eq!(format!("{token:?}"), "dst=(1:2) src=(3:4) source=none name=5");
+    }
+
+    #[test]
+    fn source_view_token_into_owned() {
+        let owned: Vec<OwnedSourceViewToken> = {
+            let sm = sample_map();
+            let table = sm.generate_lookup_table();
+            let token = sm.lookup_source_view_token(&table, 2, 3).unwrap();
+            vec![token.into_owned()]
+        };
+        let token = &owned[0];
+        assert_eq!((token.get_dst_line(), token.get_dst_col()), (2, 3));
+        assert_eq!((token.get_src_line(), token.get_src_col()), (4, 5));
+        assert_eq!(token.get_source(), Some("src0.js"));
+        assert_eq!(token.get_name(), Some("name0"));
+        assert_eq!(token.token(), Token::new(2, 3, 4, 5, Some(0), Some(0)));
+    }
 }
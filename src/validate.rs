@@ -0,0 +1,213 @@
+use std::fmt;
+
+use crate::{
+    SourceMap,
+    sourcemap::{LINE_TERMINATORS, line_starts},
+};
+
+/// A suspect-but-decodable property of a [`SourceMap`], reported by [`SourceMap::validate`].
+///
+/// Token issues carry the index of the offending token in [`SourceMap::get_tokens`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// The token references a source id past the end of `sources`.
+    InvalidSourceId { token: usize },
+    /// The token references a name id past the end of `names`.
+    InvalidNameId { token: usize },
+    /// The token's original line or column lies past the end of its source content.
+    SourcePositionOutOfBounds { token: usize },
+    /// The token's generated position precedes the previous token's, which breaks lookups.
+    UnsortedDestination { token: usize },
+    /// No token references this source.
+    UnusedSource { source_id: u32 },
+    /// No token references this name.
+    UnusedName { name_id: u32 },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSourceId { token } => {
+                write!(f, "Token {token} references a non-existing source")
+            }
+            Self::InvalidNameId { token } => {
+                write!(f, "Token {token} references a non-existing name")
+            }
+            Self::SourcePositionOutOfBounds { token } => {
+                write!(f, "Token {token} points past the end of its source content")
+            }
+            Self::UnsortedDestination { token } => {
+                write!(f, "Token {token} is positioned before the previous token")
+            }
+            Self::UnusedSource { source_id } => write!(f, "Source {source_id} is never referenced"),
+            Self::UnusedName { name_id } => write!(f, "Name {name_id} is never referenced"),
+        }
+    }
+}
+
+impl SourceMap<'_> {
+    /// Check the map for issues that decoding does not reject.
+    ///
+    /// Reports, in this order: per token (in token order) invalid ids, original positions
+    /// outside the source content and destination positions out of order; then unused sources
+    /// and unused names. Original positions are only checked for sources with content. A column
+    /// may point at the end of a line, but not into or past its line terminator; lines end at
+    /// `\n`, `\r\n`, `\r`, U+2028 and U+2029 and columns count UTF-16 code units.
+    ///
+    /// Returns an empty vector for a well-formed map.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = vec![];
+        let mut source_used = vec![false; self.sources.len()];
+        let mut name_used = vec![false; self.names.len()];
+        // Per-source UTF-16 line lengths, computed on first use.
+        let mut line_lengths: Vec<Option<Vec<u32>>> = vec![None; self.sources.len()];
+
+        for (index, token) in self.tokens.iter().enumerate() {
+            if let Some(source_id) = token.get_source_id() {
+                if let Some(used) = source_used.get_mut(source_id as usize) {
+                    *used = true;
+                    if let Some(Some(content)) = self.source_contents.get(source_id as usize) {
+                        let lines = line_lengths[source_id as usize]
+                            .get_or_insert_with(|| utf16_line_lengths(content));
+                        let in_bounds = lines
+                            .get(token.src_line as usize)
+                            .is_some_and(|&len| token.src_col <= len);
+                        if !in_bounds {
+                            issues
+                                .push(ValidationIssue::SourcePositionOutOfBounds { token: index });
+                        }
+                    }
+                } else {
+                    issues.push(ValidationIssue::InvalidSourceId { token: index });
+                }
+            }
+            if let Some(name_id) = token.get_name_id() {
+                if let Some(used) = name_used.get_mut(name_id as usize) {
+                    *used = true;
+                } else {
+                    issues.push(ValidationIssue::InvalidNameId { token: index });
+                }
+            }
+            if let Some(prev) = index.checked_sub(1).map(|i| &self.tokens[i])
+                && (token.dst_line, token.dst_col) < (prev.dst_line, prev.dst_col)
+            {
+                issues.push(ValidationIssue::UnsortedDestination { token: index });
+            }
+        }
+
+        issues.extend(
+            (0..)
+                .zip(source_used)
+                .filter(|(_, used)| !used)
+                .map(|(source_id, _)| ValidationIssue::UnusedSource { source_id }),
+        );
+        issues.extend(
+            (0..)
+                .zip(name_used)
+                .filter(|(_, used)| !used)
+                .map(|(name_id, _)| ValidationIssue::UnusedName { name_id }),
+        );
+        issues
+    }
+}
+
+/// UTF-16 length of every line in `content`, excluding line terminators.
+fn utf16_line_lengths(content: &str) -> Vec<u32> {
+    let starts = line_starts(content);
+    let ends = starts.iter().skip(1).map(|&end| end as usize).chain([content.len()]);
+    starts
+        .iter()
+        .zip(ends)
+        .map(|(&start, end)| {
+            let line = content[start as usize..end].trim_end_matches(LINE_TERMINATORS);
+            line.encode_utf16().count() as u32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::Token;
+
+    #[test]
+    fn invalid_token_position() {
+        // Same map as the `invalid_token_position` visualizer snapshot.
+        let sm = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![Cow::Borrowed("src.js")],
+            vec![Some(Cow::Borrowed("abc\ndef"))],
+            vec![
+                Token::new(0, 0, 0, 0, Some(0), None),
+                Token::new(0, 10, 0, 0, Some(0), None),
+                Token::new(0, 0, 0, 10, Some(0), None),
+            ]
+            .into_boxed_slice(),
+            None,
+        );
+        assert_eq!(
+            sm.validate(),
+            vec![
+                ValidationIssue::SourcePositionOutOfBounds { token: 2 },
+                ValidationIssue::UnsortedDestination { token: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn ids_and_unused_entries() {
+        let sm = SourceMap::new(
+            None,
+            vec![Cow::Borrowed("used"), Cow::Borrowed("unused")],
+            None,
+            vec![Cow::Borrowed("a.js"), Cow::Borrowed("b.js")],
+            // No content: original positions are not checked.
+            vec![],
+            vec![
+                Token::new(0, 0, 99, 99, Some(0), Some(0)),
+                Token::new(0, 1, 0, 0, Some(5), Some(7)),
+                Token::new(0, 2, 0, 0, None, None),
+            ]
+            .into_boxed_slice(),
+            None,
+        );
+        assert_eq!(
+            sm.validate(),
+            vec![
+                ValidationIssue::InvalidSourceId { token: 1 },
+                ValidationIssue::InvalidNameId { token: 1 },
+                ValidationIssue::UnusedSource { source_id: 1 },
+                ValidationIssue::UnusedName { name_id: 1 },
+            ]
+        );
+        assert_eq!(
+            ValidationIssue::UnusedSource { source_id: 1 }.to_string(),
+            "Source 1 is never referenced"
+        );
+    }
+
+    #[test]
+    fn line_lengths() {
+        assert_eq!(utf16_line_lengths(""), vec![0]);
+        assert_eq!(utf16_line_lengths("ab\r\n👀\rc\u{2028}d\n"), vec![2, 2, 1, 1, 0]);
+    }
+
+    #[test]
+    fn end_of_line_column_is_valid() {
+        let sm = SourceMap::new(
+            None,
+            vec![],
+            None,
+            vec![Cow::Borrowed("a.js")],
+            vec![Some(Cow::Borrowed("ab\ncd"))],
+            vec![Token::new(0, 0, 0, 2, Some(0), None), Token::new(0, 1, 1, 3, Some(0), None)]
+                .into_boxed_slice(),
+            None,
+        );
+        assert_eq!(sm.validate(), vec![ValidationIssue::SourcePositionOutOfBounds { token: 1 }]);
+    }
+}
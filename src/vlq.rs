@@ -0,0 +1,108 @@
+//! Base64 VLQ codec for single values, as used by the `mappings` field.
+//!
+//! These are safe wrappers over the encoder and decoder used for whole mappings strings, for
+//! custom mapping formats that need to read or write individual values.
+
+use crate::{decode::next_vlq, encode, error::Error, error::Result};
+
+/// The largest magnitude [`decode_vlq`] accepts: the sign bit and the magnitude together must
+/// fit into an `i64`.
+pub const MAX_VALUE: i64 = (1 << 62) - 1;
+
+/// Append the base64 VLQ encoding of `value` to `out`.
+///
+/// # Panics
+/// Panics if `value` is outside `-MAX_VALUE..=MAX_VALUE`.
+pub fn encode_vlq(value: i64, out: &mut String) {
+    assert!(value.unsigned_abs() <= MAX_VALUE as u64, "{value} is out of the VLQ range");
+    let num = (value.unsigned_abs() << 1) | u64::from(value < 0);
+    // A 64-bit VLQ integer takes at most 13 base64 chars (5 payload bits each).
+    out.reserve(13);
+    // SAFETY: 13 bytes of spare capacity were reserved above, enough for any `u64`.
+    unsafe { encode::encode_vlq(out, num) };
+}
+
+/// Decode one base64 VLQ value from the start of `input`.
+///
+/// Returns the value and the number of bytes it occupied; decoding stops there, so a following
+/// `,`, `;` or further value is left for the caller.
+///
+/// # Errors
+/// * [`Error::VlqNoValues`] when `input` is empty or starts with a `,` / `;` delimiter.
+/// * [`Error::VlqLeftover`] when `input` ends in the middle of a value.
+/// * [`Error::VlqOverflow`] when the value does not fit into an `i64`.
+pub fn decode_vlq(input: &[u8]) -> Result<(i64, usize)> {
+    let mut cursor = 0;
+    let value = next_vlq(input, &mut cursor)?.ok_or(Error::VlqNoValues)?;
+    Ok((value, cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        #[rustfmt::skip]
+        const FIXTURES: &[(i64, &str)] = &[
+            (0,                   "A"),
+            (1,                   "C"),
+            (-1,                  "D"),
+            (15,                  "e"),
+            (-15,                 "f"),
+            (16,                  "gB"),
+            (-16,                 "hB"),
+            (511,                 "+f"),
+            (512,                 "ggB"),
+            (16_777_216,          "gggggB"),
+            (536_870_912,         "ggggggB"),
+            (4_294_967_295,       "+/////H"),
+            (-4_294_967_295,      "//////H"),
+        ];
+        for &(value, encoded) in FIXTURES {
+            let mut out = String::new();
+            encode_vlq(value, &mut out);
+            assert_eq!(out, encoded, "encoding {value}");
+            assert_eq!(decode_vlq(encoded.as_bytes()).unwrap(), (value, encoded.len()));
+        }
+    }
+
+    #[test]
+    fn encode_appends() {
+        let mut out = String::from("AA,");
+        encode_vlq(16, &mut out);
+        encode_vlq(-1, &mut out);
+        assert_eq!(out, "AA,gBD");
+    }
+
+    #[test]
+    fn large_values() {
+        for value in [MAX_VALUE, -MAX_VALUE, 1 << 40, -(1 << 40)] {
+            let mut out = String::new();
+            encode_vlq(value, &mut out);
+            assert_eq!(decode_vlq(out.as_bytes()).unwrap(), (value, out.len()), "{value}");
+        }
+    }
+
+    #[test]
+    fn decode_stops_after_one_value() {
+        assert_eq!(decode_vlq(b"gBC,A").unwrap(), (16, 2));
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert!(matches!(decode_vlq(b""), Err(Error::VlqNoValues)));
+        assert!(matches!(decode_vlq(b";A"), Err(Error::VlqNoValues)));
+        assert!(matches!(decode_vlq(b"g"), Err(Error::VlqLeftover)));
+        assert!(matches!(decode_vlq(b"g,"), Err(Error::VlqLeftover)));
+        assert!(matches!(decode_vlq(b"gggggggggggggggB"), Err(Error::VlqOverflow)));
+        // One past `MAX_VALUE`: the 13th char carries a bit beyond the `i64` magnitude.
+        assert!(matches!(decode_vlq(b"ggggggggggggQ"), Err(Error::VlqOverflow)));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of the VLQ range")]
+    fn encode_out_of_range_panics() {
+        encode_vlq(MAX_VALUE + 1, &mut String::new());
+    }
+}
@@ -1,6 +1,11 @@
 use std::fs;
 
-use oxc_sourcemap::{SourceMap, SourcemapVisualizer, Token};
+use oxc_sourcemap::{SourceMap, SourceViewToken, SourcemapVisualizer, Token};
+
+fn load_fixture(dir: &str, file: &str) -> String {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    fs::read_to_string(path.join(dir).join(file)).unwrap()
+}
 
 #[test]
 fn snapshot_sourcemap_visualizer() {
@@ -16,6 +21,62 @@ fn snapshot_sourcemap_visualizer() {
     });
 }
 
+#[test]
+fn mappings_json() {
+    let js = load_fixture("basic", "test.js");
+    let js_map = load_fixture("basic", "test.js.map");
+    let sourcemap = SourceMap::from_json_string(&js_map).unwrap();
+    let visualizer = SourcemapVisualizer::new(&js, &sourcemap);
+
+    let json = visualizer.to_mappings_json();
+    let mappings: serde_json::Value = serde_json::from_str(&json).unwrap();
+    // One entry per `-->` line of the text visualizer.
+    let text_lines = visualizer.get_text().lines().filter(|line| line.contains("-->")).count();
+    assert_eq!(mappings.as_array().unwrap().len(), text_lines);
+    insta::assert_snapshot!("mappings_json", json);
+}
+
+#[test]
+fn tokens_on_line() {
+    let js_map = load_fixture("esbuild", "example.js.map");
+    let sourcemap = SourceMap::from_json_string(&js_map).unwrap();
+    let table = sourcemap.generate_lookup_table();
+
+    let mappings = sourcemap.to_json().mappings;
+    for (line, segments) in mappings.split(';').enumerate() {
+        let tokens = sourcemap.tokens_on_line(&table, line as u32);
+        let expected = segments.split(',').filter(|segment| !segment.is_empty()).count();
+        assert_eq!(tokens.len(), expected, "line {line}");
+        assert!(tokens.iter().all(|token| token.get_dst_line() == line as u32));
+    }
+    assert!(sourcemap.tokens_on_line(&table, u32::MAX).is_empty());
+}
+
+#[test]
+fn iter_lines() {
+    let js_map = load_fixture("esbuild", "example.js.map");
+    let sourcemap = SourceMap::from_json_string(&js_map).unwrap();
+
+    // Exactly the generated lines with at least one segment, in order.
+    let mappings = sourcemap.to_json().mappings;
+    let expected: Vec<u32> = (0..)
+        .zip(mappings.split(';'))
+        .filter(|(_, segments)| !segments.is_empty())
+        .map(|(line, _)| line)
+        .collect();
+    let lines: Vec<u32> = sourcemap.iter_lines().map(|(line, _)| line).collect();
+    assert_eq!(lines, expected);
+
+    let table = sourcemap.generate_lookup_table();
+    for (line, tokens) in sourcemap.iter_lines() {
+        assert_eq!(tokens, sourcemap.tokens_on_line(&table, line));
+    }
+    assert_eq!(
+        sourcemap.iter_lines().map(|(_, tokens)| tokens.len()).sum::<usize>(),
+        sourcemap.get_tokens().len()
+    );
+}
+
 #[test]
 fn invalid_token_position() {
     let sourcemap = SourceMap::new(
@@ -37,3 +98,109 @@ fn invalid_token_position() {
     let visualizer_text = visualizer.get_text();
     insta::assert_snapshot!("invalid_token_position", visualizer_text);
 }
+
+#[test]
+fn next_in_line() {
+    let js_map = load_fixture("esbuild", "example.js.map");
+    let sourcemap = SourceMap::from_json_string(&js_map).unwrap();
+
+    // The first token (line 1, from `AAAA,SAAS`) covers generated columns 0..9.
+    let first = sourcemap.get_source_view_token(0).unwrap();
+    assert_eq!(first.dst_position(), (1, 0));
+    assert_eq!(first.next_in_line().map(|next| next.dst_position()), Some((1, 9)));
+
+    for (_, tokens) in sourcemap.iter_lines() {
+        let views: Vec<_> =
+            tokens.iter().map(|&token| SourceViewToken::new(token, &sourcemap)).collect();
+        for pair in views.windows(2) {
+            let next = pair[0].next_in_line().unwrap();
+            assert_eq!(next.dst_position(), pair[1].dst_position());
+        }
+        assert!(views.last().unwrap().next_in_line().is_none());
+    }
+}
+
+#[test]
+fn from_json_slice() {
+    let bytes = load_fixture("perf", "real_large.map").into_bytes();
+    let owned = SourceMap::from_json(serde_json::from_slice(&bytes).unwrap()).unwrap();
+    let borrowed = SourceMap::from_json_slice(&bytes).unwrap();
+    assert!(borrowed.get_tokens().len() > 0);
+    assert_eq!(borrowed.get_tokens().collect::<Vec<_>>(), owned.get_tokens().collect::<Vec<_>>());
+    assert_eq!(borrowed.to_json_string(), owned.to_json_string());
+
+    assert!(
+        SourceMap::from_json_slice(b"{\"version\":3,\"sources\":[\"\xff\"],\"mappings\":\"\"}")
+            .is_err()
+    );
+}
+
+#[test]
+fn name_and_source_index() {
+    let js_map = load_fixture("esbuild", "example.js.map");
+    let sourcemap = SourceMap::from_json_string(&js_map).unwrap();
+
+    // esbuild repeats names per source; the first occurrence wins.
+    assert_eq!(sourcemap.name_index("h"), Some(0));
+    assert_eq!(sourcemap.name_index("render"), Some(2));
+    assert_eq!(sourcemap.name_index("label_"), Some(15));
+    for name in sourcemap.get_names() {
+        let id = sourcemap.name_index(name).unwrap();
+        assert_eq!(sourcemap.get_name(id), Some(name));
+    }
+    for (id, source) in sourcemap.get_sources().enumerate() {
+        assert_eq!(sourcemap.source_index(source), Some(id as u32), "{source}");
+    }
+    assert_eq!(sourcemap.name_index("not a name"), None);
+    assert_eq!(sourcemap.source_index("missing.js"), None);
+}
+
+#[test]
+fn tokens_for_source() {
+    let js_map = load_fixture("basic", "test.js.map");
+    let sourcemap = SourceMap::from_json_string(&js_map).unwrap();
+
+    let mut total = 0;
+    for source_id in 0..sourcemap.get_sources().len() as u32 {
+        let tokens: Vec<Token> = sourcemap.tokens_for_source(source_id).collect();
+        assert!(!tokens.is_empty());
+        assert!(tokens.iter().all(|token| token.get_source_id() == Some(source_id)));
+        assert!(tokens.is_sorted());
+        total += tokens.len();
+    }
+    let unmapped = sourcemap.get_tokens().filter(|token| token.get_source_id().is_none()).count();
+    assert_eq!(total + unmapped, sourcemap.get_tokens().len());
+    assert_eq!(sourcemap.tokens_for_source(99).count(), 0);
+}
+
+#[test]
+fn content_hash() {
+    let js_map = load_fixture("esbuild", "example.js.map");
+    let copy = js_map.clone();
+    let first = SourceMap::from_json_string(&js_map).unwrap();
+    let second = SourceMap::from_json_string(&copy).unwrap();
+    assert_eq!(first.content_hash(), second.content_hash());
+
+    // Going through an owned copy and rechunking doesn't change the hash.
+    let mut owned = SourceMap::from_json_string(&first.to_json_string()).unwrap().into_owned();
+    owned.rechunk(4);
+    assert_eq!(owned.content_hash(), first.content_hash());
+
+    let mut renamed = SourceMap::from_json_string(&js_map).unwrap();
+    renamed.set_sources(["other.js"]);
+    assert_ne!(renamed.content_hash(), first.content_hash());
+}
+
+#[test]
+fn token_counts_by_source() {
+    let js_map = load_fixture("basic", "test.js.map");
+    let sourcemap = SourceMap::from_json_string(&js_map).unwrap();
+
+    let counts = sourcemap.token_counts_by_source();
+    assert_eq!(counts, [3, 8]);
+    for (source_id, &count) in counts.iter().enumerate() {
+        assert_eq!(count, sourcemap.tokens_for_source(source_id as u32).count());
+    }
+    let unmapped = sourcemap.get_tokens().filter(|token| token.get_source_id().is_none()).count();
+    assert_eq!(counts.iter().sum::<usize>() + unmapped, sourcemap.get_tokens().len());
+}